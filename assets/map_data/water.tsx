@@ -39,4 +39,9 @@
    <frame tileid="54" duration="100"/>
   </animation>
  </tile>
+ <tile id="56">
+  <properties>
+   <property name="solid" type="bool" value="true"/>
+  </properties>
+ </tile>
 </tileset>
@@ -0,0 +1,27 @@
+// Imported by asset path from every shader that shades with the scene's lights
+
+// Keep in sync with GpuLight2d
+struct Light2d {
+    position: vec2<f32>,
+    radius: f32,
+    intensity: f32,
+    color: vec3<f32>,
+};
+
+// How far above the scene the lights hang, lower values make normal maps more pronounced
+const LIGHT_HEIGHT: f32 = 48.0;
+
+// What one light adds at this point, fading out smoothly towards its radius
+fn light_contribution(light: Light2d, world_pos: vec2<f32>, normal: vec3<f32>) -> vec3<f32> {
+    let to_light = light.position - world_pos;
+    let distance = length(to_light);
+    if (distance >= light.radius) {
+        return vec3<f32>(0.0);
+    }
+    let window = 1.0 - (distance * distance) / (light.radius * light.radius);
+    let attenuation = window * window;
+    // Never lower than 45 degrees, so far off lights like the moon still reach flat surfaces
+    let light_dir = normalize(vec3<f32>(to_light, max(LIGHT_HEIGHT, distance)));
+    let diffuse = max(dot(normal, light_dir), 0.0);
+    return light.color * light.intensity * attenuation * diffuse;
+}
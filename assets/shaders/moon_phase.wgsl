@@ -0,0 +1,44 @@
+#import bevy_sprite::mesh2d_vertex_output::VertexOutput
+
+struct MoonPhaseMaterial {
+    phase: f32,
+    earthshine: f32,
+    // where the disc sits in the texture, in uv units
+    disc_center: vec2<f32>,
+    disc_radius: f32,
+};
+
+@group(2) @binding(0) var<uniform> material: MoonPhaseMaterial;
+@group(2) @binding(1) var moon_texture: texture_2d<f32>;
+@group(2) @binding(2) var moon_sampler: sampler;
+
+const TAU: f32 = 6.28318530718;
+// Width of the soft edge along the terminator, in disc units
+const TERMINATOR_SOFTNESS: f32 = 0.08;
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    let tex_color = textureSample(moon_texture, moon_sampler, in.uv);
+
+    // Position on the moon's disc, -1..1 on both axes inside it
+    let disc = (in.uv - material.disc_center) / material.disc_radius;
+    // The texture's own alpha takes care of the background, the mask only applies to the disc
+    if (length(disc) > 1.0) {
+        return tex_color;
+    }
+    // Half width of the disc at this height, the terminator is an ellipse spanning it
+    let half_width = sqrt(max(1.0 - disc.y * disc.y, 0.0));
+
+    // Waxing lights up from the right edge, waning goes dark from the right edge too
+    var lit: f32;
+    if (material.phase < 0.5) {
+        let terminator = half_width * cos(material.phase * TAU);
+        lit = smoothstep(terminator - TERMINATOR_SOFTNESS, terminator + TERMINATOR_SOFTNESS, disc.x);
+    } else {
+        let terminator = half_width * cos((material.phase - 0.5) * TAU);
+        lit = 1.0 - smoothstep(terminator - TERMINATOR_SOFTNESS, terminator + TERMINATOR_SOFTNESS, disc.x);
+    }
+    let light = mix(material.earthshine, 1.0, lit);
+
+    return vec4<f32>(tex_color.rgb * light, tex_color.a);
+}
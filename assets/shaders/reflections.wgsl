@@ -1,27 +1,35 @@
+#import bevy_sprite::mesh2d_vertex_output::VertexOutput
+#import "shaders/lighting.wgsl"::{Light2d, light_contribution}
+
 struct ReflectionMaterial {
-    light_position: vec2<f32>,
-    light_intensity: f32,
-    light_color: vec3<f32>,
+    normal_map_strength: f32, // 0 ignores the normal map
 };
 
-@group(1) @binding(0) var<uniform> material: ReflectionMaterial;
-@group(1) @binding(1) var texture_diffuse: texture_2d<f32>;
-@group(1) @binding(2) var texture_diffuse_sampler: sampler;
+@group(2) @binding(0) var<uniform> material: ReflectionMaterial;
+@group(2) @binding(1) var texture_diffuse: texture_2d<f32>;
+@group(2) @binding(2) var texture_diffuse_sampler: sampler;
+@group(2) @binding(3) var<storage, read> lights: array<Light2d>;
+@group(2) @binding(4) var normal_map: texture_2d<f32>;
+@group(2) @binding(5) var normal_map_sampler: sampler;
+
+// Sum of every light reaching this point, the loop lives with the binding since each material
+// has it in a different group
+fn point_lighting(world_pos: vec2<f32>, normal: vec3<f32>) -> vec3<f32> {
+    var total = vec3<f32>(0.0);
+    for (var i: u32 = 0u; i < arrayLength(&lights); i = i + 1u) {
+        total = total + light_contribution(lights[i], world_pos, normal);
+    }
+    return total;
+}
 
 @fragment
 fn fragment(input: VertexOutput) -> @location(0) vec4<f32> {
     let tex_color = textureSample(texture_diffuse, texture_diffuse_sampler, input.uv);
 
-    // Calculate the distance from the current fragment to the light source
-    let light_dir = material.light_position - input.uv;
-    let distance = length(light_dir);
-
-    // Simple attenuation based on distance
-    let attenuation = material.light_intensity / (distance * distance);
-
-    // Calculate lighting
-    let lighting = attenuation * material.light_color;
+    let sampled_normal = textureSample(normal_map, normal_map_sampler, input.uv).xyz * 2.0 - 1.0;
+    let normal = normalize(mix(vec3<f32>(0.0, 0.0, 1.0), sampled_normal, material.normal_map_strength));
 
     // Apply lighting to the texture color
+    let lighting = point_lighting(input.world_position.xy, normal);
     return vec4<f32>(tex_color.rgb * lighting, tex_color.a);
 }
@@ -0,0 +1,51 @@
+#import bevy_ecs_tilemap::vertex_output::MeshVertexOutput
+#import bevy_ecs_tilemap::common::process_fragment
+#import "shaders/tile_common.wgsl"::{Fog, tile_world_position, light_tile, apply_fog, fbm}
+
+struct WaterMaterial {
+    fog: Fog,
+    normal_map_strength: f32, // 0 ignores the normal map
+    glint_origin: vec2<f32>, // Below the moon, the path runs up the screen from here
+    glint_width: f32,
+    glint_length: f32,
+    glint_intensity: f32, // 0 turns the glint off
+    glint_color: vec3<f32>,
+};
+
+@group(3) @binding(0)
+var<uniform> material: WaterMaterial;
+
+// Shimmering streak of moonlight on the water, widest and brightest closest to the moon
+fn moon_glint(world_pos: vec2<f32>, time: f32) -> f32 {
+    let offset = world_pos - material.glint_origin;
+    // 0 at the origin, 1 at the far end of the path
+    let along = clamp(offset.y / material.glint_length, 0.0, 1.0);
+    if (offset.y < 0.0 || offset.y > material.glint_length) {
+        return 0.0;
+    }
+
+    // The path wobbles a little sideways and narrows away from the moon
+    let wobble = (fbm(vec2<f32>(world_pos.y * 0.02, time * 0.5), 3) - 0.5) * material.glint_width;
+    let width = material.glint_width * mix(0.4, 1.0, along);
+    let across = (offset.x + wobble) / width;
+    let path = exp(-across * across);
+
+    // Broken up into small stretched sparkles that drift over time
+    let sparkle_pos = vec2<f32>(world_pos.x * 0.15, world_pos.y * 0.6 - time * 2.0);
+    let sparkle = smoothstep(0.55, 0.8, fbm(sparkle_pos, 4));
+
+    return path * sparkle * along * material.glint_intensity;
+}
+
+@fragment
+fn fragment(in: MeshVertexOutput) -> @location(0) vec4<f32> {
+    // Process the base color using existing fragment processing
+    let base_color = process_fragment(in);
+    let world_pos = tile_world_position(in);
+
+    let lit_color = light_tile(in, base_color.rgb, world_pos, material.fog, material.normal_map_strength);
+    let glinting_color = lit_color + material.glint_color * moon_glint(world_pos, material.fog.time);
+    let final_color = apply_fog(glinting_color, world_pos, material.fog);
+
+    return vec4(final_color, base_color.a);
+}
@@ -1,5 +1,6 @@
 use bevy::{
-    app::{App, FixedUpdate, PluginGroup, Update},
+    app::{App, FixedUpdate, PluginGroup, PreUpdate, Startup, Update},
+    input::InputSystem,
     math::Vec2,
     prelude::{
         in_state, AppExtStates, Commands, IntoSystemConfigs, OnEnter, OnExit, ParamSet, Query,
@@ -22,10 +23,20 @@ use yakuzaishi::{
         camera_2d_sys::{bottom_camera, top_camera},
         camera_components::BottomCameraTag,
     },
-    environment::moon::{place_moon, MoonAsset, MoonLightSource, MoonTag},
+    environment::moon::{place_moon, MoonAsset},
+    input::input_sys::{gather_input_actions, load_input_config},
     kinetic_components::PlayerEntityTag,
-    materials::reflections::ReflectionMaterial,
-    player::player_sys::{control_player_entity, spawn_player_entity},
+    kinetic_res::{JumpSettings, SwimPhysicsSettings},
+    kinetic_sys::{integrate_kinetic_entities, interpolate_kinetic_transforms, SplashEvent},
+    lighting::{
+        lighting_res::{LightingSettings, SceneLights},
+        lighting_sys::{apply_lights_to_materials, collect_lights},
+    },
+    materials::{moon_phase::MoonPhaseMaterial, reflections::ReflectionMaterial},
+    player::{
+        player_res::CoopSettings,
+        player_sys::{control_player_entity, spawn_player_entity},
+    },
     NINTENDO_DS_SCREEN_HEIGHT, NINTENDO_DS_SCREEN_WIDTH,
 };
 
@@ -50,7 +61,16 @@ fn main() {
         .add_plugins((
             // WorldInspectorPlugin::new(),
             Material2dPlugin::<ReflectionMaterial>::default(),
+            Material2dPlugin::<MoonPhaseMaterial>::default(),
         ))
+        .init_resource::<SwimPhysicsSettings>()
+        .init_resource::<JumpSettings>()
+        .init_resource::<CoopSettings>()
+        .init_resource::<SceneLights>()
+        .init_resource::<LightingSettings>()
+        .add_event::<SplashEvent>()
+        .add_systems(Startup, load_input_config)
+        .add_systems(PreUpdate, gather_input_actions.after(InputSystem))
         .init_state::<GameState>()
         .add_loading_state(
             LoadingState::new(GameState::Load)
@@ -65,33 +85,45 @@ fn main() {
         .add_systems(OnEnter(GameState::Run), (top_camera, bottom_camera))
         .add_systems(
             FixedUpdate,
-            control_player_entity.run_if(in_state(GameState::Run)),
+            (control_player_entity, integrate_kinetic_entities)
+                .chain()
+                .run_if(in_state(GameState::Run)),
+        )
+        .add_systems(
+            Update,
+            (interpolate_kinetic_transforms, track_camera_ds)
+                .chain()
+                .run_if(in_state(GameState::Run)),
+        )
+        .add_systems(
+            Update,
+            (collect_lights, apply_lights_to_materials)
+                .chain()
+                .run_if(in_state(GameState::Run)),
         )
-        .add_systems(Update, track_camera_ds.run_if(in_state(GameState::Run)))
         .run();
 }
 
+// the moon's light reaches it through collect_lights and apply_lights_to_materials
 fn setup_reflection_material(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ReflectionMaterial>>,
     moon_asset: Res<MoonAsset>,
-    query: Query<&MoonLightSource, With<MoonTag>>,
+    scene_lights: Res<SceneLights>,
 ) {
-    if let Some(moonlight) = query.iter().next() {
-        let moon_image = moon_asset.background.clone();
-        commands.spawn(MaterialMesh2dBundle {
-            mesh: meshes.add(Rectangle::new(100.0, 100.0)).into(),
-            material: materials.add(ReflectionMaterial {
-                light_position: moonlight.position,
-                light_intensity: moonlight.intensity,
-                light_color: moonlight.color,
-                texture: moon_image,
-            }),
-            transform: Transform::from_xyz(0.0, NINTENDO_DS_SCREEN_HEIGHT, 0.0),
-            ..default()
-        });
-    }
+    let moon_image = moon_asset.texture.clone();
+    commands.spawn(MaterialMesh2dBundle {
+        mesh: meshes.add(Rectangle::new(100.0, 100.0)).into(),
+        material: materials.add(ReflectionMaterial {
+            normal_map_strength: 0.0,
+            texture: moon_image,
+            lights: scene_lights.gpu_lights(),
+            normal_map: None,
+        }),
+        transform: Transform::from_xyz(0.0, NINTENDO_DS_SCREEN_HEIGHT, 0.0),
+        ..default()
+    });
 }
 
 #[derive(States, Default, Debug, Clone, PartialEq, Eq, Hash)]
@@ -0,0 +1,134 @@
+use std::time::{Duration, Instant};
+
+use bevy::{
+    app::{App, Update},
+    math::Vec3,
+    prelude::{IntoSystemConfigs, Timer, TimerMode, World},
+    MinimalPlugins,
+};
+use bevy_ecs_tilemap::{
+    map::{TilemapId, TilemapSize},
+    prelude::{TilePos, TileStorage, TileTextureIndex},
+};
+use yakuzaishi::{
+    anime::{
+        anime_components::{AnimationComponent, AnimationTimer},
+        anime_res::RippleSettings,
+        frame_event_sys::AnimationFrameEvent,
+        map_anime_sys::{
+            animate_tile_ripples, handle_overlap_event, spread_tile_ripples,
+            start_pending_tile_ripples,
+        },
+    },
+    kinetic_components::{KineticEntityComponents, PlayerEntityTag},
+    map::{
+        tile_overlap_sys::{
+            attach_tile_occupancy, emit_tile_overlap_events, TileEntered, TileExited, TileStay,
+        },
+        tiled_components::TileEntityTag,
+    },
+    TILE_ANIMATION_SPEED, TILE_ANIMATION_TEXTURE_END_IDX, TILE_ANIMATION_TEXTURE_START_IDX,
+    TILE_SIZE,
+};
+
+// run with `cargo run --release --example tile_overlap_bench`
+// the per frame cost should stay flat as the generated map grows
+const MAP_SIZES: [u32; 4] = [16, 64, 256, 512];
+const WARMUP_FRAMES: u32 = 10;
+const MEASURED_FRAMES: u32 = 500;
+
+fn main() {
+    for map_size in MAP_SIZES {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<RippleSettings>()
+            .add_event::<AnimationFrameEvent>()
+            .add_event::<TileEntered>()
+            .add_event::<TileExited>()
+            .add_event::<TileStay>()
+            .add_systems(
+                Update,
+                (
+                    attach_tile_occupancy,
+                    emit_tile_overlap_events,
+                    handle_overlap_event,
+                    start_pending_tile_ripples,
+                    spread_tile_ripples,
+                    animate_tile_ripples,
+                )
+                    .chain(),
+            );
+
+        spawn_generated_map(app.world_mut(), map_size);
+        spawn_player(app.world_mut(), map_size);
+
+        for _ in 0..WARMUP_FRAMES {
+            app.update();
+        }
+
+        let start = Instant::now();
+        for _ in 0..MEASURED_FRAMES {
+            app.update();
+        }
+        let per_frame: Duration = start.elapsed() / MEASURED_FRAMES;
+
+        println!(
+            "{:>3}x{:<3} map ({:>6} animated tiles): {:?} per frame",
+            map_size,
+            map_size,
+            map_size * map_size,
+            per_frame
+        );
+    }
+}
+
+fn spawn_generated_map(world: &mut World, map_size: u32) {
+    let tilemap_size = TilemapSize {
+        x: map_size,
+        y: map_size,
+    };
+    let tilemap_entity = world.spawn_empty().id();
+    let mut tile_storage = TileStorage::empty(tilemap_size);
+
+    // every tile is animated to make the old "scan every tile" approach as slow as possible
+    for x in 0..map_size {
+        for y in 0..map_size {
+            let tile_pos = TilePos { x, y };
+            let tile_entity = world
+                .spawn((
+                    tile_pos,
+                    TilemapId(tilemap_entity),
+                    TileTextureIndex(TILE_ANIMATION_TEXTURE_START_IDX),
+                    AnimationComponent {
+                        start_idx: TILE_ANIMATION_TEXTURE_START_IDX,
+                        end_idx: TILE_ANIMATION_TEXTURE_END_IDX,
+                        speed: TILE_ANIMATION_SPEED,
+                    },
+                    AnimationTimer(Timer::from_seconds(
+                        TILE_ANIMATION_SPEED,
+                        TimerMode::Repeating,
+                    )),
+                    TileEntityTag,
+                ))
+                .id();
+            tile_storage.set(&tile_pos, tile_entity);
+        }
+    }
+
+    world.entity_mut(tilemap_entity).insert(tile_storage);
+}
+
+fn spawn_player(world: &mut World, map_size: u32) {
+    let center = (map_size / 2) as f32 * TILE_SIZE;
+    let position = Vec3::new(center, center, 0.0);
+    world.spawn((
+        KineticEntityComponents {
+            y_axis_displacement: 0.0,
+            x_axis_displacement: 0.0,
+            position,
+            prev_position: position,
+            heading: 0.0,
+        },
+        PlayerEntityTag,
+    ));
+}
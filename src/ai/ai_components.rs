@@ -0,0 +1,61 @@
+use bevy::{math::Vec2, prelude::Component};
+use bevy_ecs_tilemap::tiles::TilePos;
+
+use crate::ai::path_res::Connectivity;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SteeringBehavior {
+    // meander around aimlessly
+    Wander,
+    // swim to a point and stay there
+    Seek { target: Vec2 },
+    // keep away from the closest player, wander once they're far enough
+    FleePlayer,
+    // tag along behind the closest player
+    FollowPlayer,
+    // circle around a point
+    Orbit { center: Vec2, radius: f32 },
+}
+
+// picks this entity's SteeringInput every fixed tick
+#[derive(Component, Debug)]
+pub struct SteeringAgent {
+    pub behavior: SteeringBehavior,
+    // radians, where the wander behavior currently wants to go
+    pub wander_angle: f32,
+}
+
+impl SteeringAgent {
+    pub fn new(behavior: SteeringBehavior) -> Self {
+        Self {
+            behavior,
+            wander_angle: 0.0,
+        }
+    }
+}
+
+// boids only flock with members of the same school, the id is the spawner's tiled object id
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FlockMember {
+    pub flock: u32,
+}
+
+// ask the pathfinding queue for a route to goal, swapped for FollowPath or PathNotFound when done
+#[derive(Component, Clone, Copy, Debug)]
+pub struct PathRequest {
+    pub goal: TilePos,
+    pub connectivity: Connectivity,
+}
+
+// overrides the entity's SteeringInput until the last tile is reached, then gets removed
+#[derive(Component, Clone, Debug)]
+pub struct FollowPath {
+    pub tiles: Vec<TilePos>,
+    pub next: usize,
+    pub connectivity: Connectivity,
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+pub struct PathNotFound {
+    pub goal: TilePos,
+}
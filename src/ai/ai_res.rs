@@ -0,0 +1,107 @@
+use bevy::{
+    math::{IVec2, Vec2},
+    prelude::Resource,
+    utils::HashMap,
+};
+
+use crate::TILE_SIZE;
+
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct SteeringBehaviorSettings {
+    // radians per second the wander direction can drift either way
+    pub wander_jitter: f32,
+    // closer than this to a seek target counts as arrived
+    pub arrive_radius: f32,
+    // players within this distance get fled from
+    pub flee_radius: f32,
+    // followers stop pushing once they're this close to the player
+    pub follow_distance: f32,
+    // how far ahead of the entity a tile gets checked for walls
+    pub collision_lookahead: f32,
+}
+
+impl Default for SteeringBehaviorSettings {
+    fn default() -> Self {
+        Self {
+            wander_jitter: 3.0,
+            arrive_radius: TILE_SIZE / 4.0,
+            flee_radius: TILE_SIZE * 3.0,
+            follow_distance: TILE_SIZE * 1.5,
+            collision_lookahead: TILE_SIZE * 0.75,
+        }
+    }
+}
+
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct FlockingSettings {
+    // school mates within this distance count as neighbours, also the spatial grid cell size
+    pub neighbour_radius: f32,
+    // neighbours closer than this get pushed away from
+    pub separation_radius: f32,
+    pub separation_weight: f32,
+    pub alignment_weight: f32,
+    pub cohesion_weight: f32,
+    // how much of the member's own SteeringAgent behavior gets mixed in
+    pub behavior_weight: f32,
+}
+
+impl Default for FlockingSettings {
+    fn default() -> Self {
+        Self {
+            neighbour_radius: TILE_SIZE,
+            separation_radius: TILE_SIZE * 0.4,
+            separation_weight: 1.5,
+            alignment_weight: 1.0,
+            cohesion_weight: 0.8,
+            behavior_weight: 0.5,
+        }
+    }
+}
+
+// buckets points into square cells so a neighbour lookup only has to visit the 3x3 cells around it
+#[derive(Resource, Debug)]
+pub struct SpatialGrid {
+    pub cell_size: f32,
+    cells: HashMap<IVec2, Vec<usize>>,
+}
+
+impl Default for SpatialGrid {
+    fn default() -> Self {
+        Self::new(TILE_SIZE)
+    }
+}
+
+impl SpatialGrid {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::default(),
+        }
+    }
+
+    // empties the cells but keeps their allocations around for the next rebuild
+    pub fn clear(&mut self) {
+        for cell in self.cells.values_mut() {
+            cell.clear();
+        }
+    }
+
+    pub fn insert(&mut self, index: usize, position: Vec2) {
+        let cell = self.cell_of(position);
+        self.cells.entry(cell).or_default().push(index);
+    }
+
+    // everything in the surrounding cells, callers still have to check the actual distance
+    pub fn nearby(&self, position: Vec2) -> impl Iterator<Item = usize> + '_ {
+        let center = self.cell_of(position);
+        (-1..=1)
+            .flat_map(move |y| (-1..=1).map(move |x| center + IVec2::new(x, y)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+    }
+
+    fn cell_of(&self, position: Vec2) -> IVec2 {
+        (position / self.cell_size).floor().as_ivec2()
+    }
+}
@@ -0,0 +1,143 @@
+use std::f32::consts::FRAC_PI_4;
+
+use bevy::{
+    math::{Vec2, Vec3},
+    prelude::{Fixed, Query, Res, ResMut, Time, With, Without},
+};
+use rand::Rng;
+
+use crate::{
+    ai::{
+        ai_components::{SteeringAgent, SteeringBehavior},
+        ai_res::SteeringBehaviorSettings,
+    },
+    kinetic_components::{KineticEntityComponents, PlayerEntityTag, SteeringInput},
+    map::tiled_res::TileCollisionMap,
+    rng_res::GameRng,
+};
+
+// has to run in FixedUpdate before integrate_kinetic_entities, agents steer in SteeringMode::Strafe terms
+pub fn run_steering_agents(
+    fixed_time: Res<Time<Fixed>>,
+    settings: Res<SteeringBehaviorSettings>,
+    collision_map: Option<Res<TileCollisionMap>>,
+    mut game_rng: ResMut<GameRng>,
+    player_query: Query<&KineticEntityComponents, With<PlayerEntityTag>>,
+    mut agent_query: Query<
+        (
+            &KineticEntityComponents,
+            &mut SteeringAgent,
+            &mut SteeringInput,
+        ),
+        Without<PlayerEntityTag>,
+    >,
+) {
+    let delta_seconds = fixed_time.delta_seconds();
+
+    for (kinetics, mut agent, mut steering_input) in agent_query.iter_mut() {
+        let position = kinetics.position.truncate();
+        let closest_player = player_query
+            .iter()
+            .map(|player| player.position.truncate())
+            .min_by(|a, b| {
+                a.distance_squared(position)
+                    .total_cmp(&b.distance_squared(position))
+            });
+
+        let behavior = agent.behavior;
+        let mut wandering = false;
+        let desired = match behavior {
+            SteeringBehavior::Wander => {
+                wandering = true;
+                wander(&mut agent, &mut game_rng, &settings, delta_seconds)
+            }
+            SteeringBehavior::Seek { target } => seek(position, target, settings.arrive_radius),
+            SteeringBehavior::FleePlayer => match closest_player {
+                Some(player) if player.distance(position) < settings.flee_radius => {
+                    (position - player).normalize_or_zero()
+                }
+                _ => {
+                    wandering = true;
+                    wander(&mut agent, &mut game_rng, &settings, delta_seconds)
+                }
+            },
+            SteeringBehavior::FollowPlayer => closest_player.map_or(Vec2::ZERO, |player| {
+                seek(position, player, settings.follow_distance)
+            }),
+            SteeringBehavior::Orbit { center, radius } => orbit(position, center, radius),
+        };
+
+        let steer = match &collision_map {
+            Some(collision_map) => avoid_walls(
+                collision_map,
+                kinetics.position,
+                desired,
+                settings.collision_lookahead,
+            ),
+            None => desired,
+        };
+        // bumping into a wall while wandering picks the new direction to wander off in
+        if wandering && steer != Vec2::ZERO {
+            agent.wander_angle = steer.to_angle();
+        }
+        steering_input.0 = steer;
+    }
+}
+
+fn wander(
+    agent: &mut SteeringAgent,
+    game_rng: &mut GameRng,
+    settings: &SteeringBehaviorSettings,
+    delta_seconds: f32,
+) -> Vec2 {
+    agent.wander_angle +=
+        game_rng.rng.gen_range(-1.0..=1.0) * settings.wander_jitter * delta_seconds;
+    Vec2::from_angle(agent.wander_angle)
+}
+
+fn seek(position: Vec2, target: Vec2, arrive_radius: f32) -> Vec2 {
+    let to_target = target - position;
+    if to_target.length() <= arrive_radius {
+        return Vec2::ZERO;
+    }
+    to_target.normalize()
+}
+
+// tangent to the circle, plus a pull back onto it the further off the radius the entity is
+fn orbit(position: Vec2, center: Vec2, radius: f32) -> Vec2 {
+    let from_center = position - center;
+    let distance = from_center.length();
+    if distance <= f32::EPSILON {
+        return Vec2::X;
+    }
+    let outward = from_center / distance;
+    let correction = ((radius - distance) / radius.max(1.0)).clamp(-1.0, 1.0);
+    (outward.perp() + outward * correction).normalize_or_zero()
+}
+
+// if the tile ahead is blocked, fan out to either side until a free direction turns up
+pub fn avoid_walls(
+    collision_map: &TileCollisionMap,
+    position: Vec3,
+    desired: Vec2,
+    lookahead: f32,
+) -> Vec2 {
+    let Some(direction) = desired.try_normalize() else {
+        return Vec2::ZERO;
+    };
+    let is_free = |direction: Vec2| {
+        collision_map.is_walkable_at(&(position + (direction * lookahead).extend(0.0)))
+    };
+    if is_free(direction) {
+        return desired;
+    }
+    for step in 1..=3 {
+        for side in [1.0, -1.0] {
+            let candidate = Vec2::from_angle(side * step as f32 * FRAC_PI_4).rotate(direction);
+            if is_free(candidate) {
+                return candidate;
+            }
+        }
+    }
+    -direction
+}
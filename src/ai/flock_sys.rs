@@ -0,0 +1,108 @@
+use bevy::{
+    math::Vec2,
+    prelude::{Entity, Query, Res, ResMut, With},
+};
+
+use crate::{
+    ai::{
+        ai_components::FlockMember,
+        ai_res::{FlockingSettings, SpatialGrid, SteeringBehaviorSettings},
+        ai_sys::avoid_walls,
+    },
+    kinetic_components::{EnvironmentEntityTag, KineticEntityComponents, SteeringInput},
+    map::tiled_res::TileCollisionMap,
+};
+
+struct Boid {
+    entity: Entity,
+    flock: u32,
+    position: Vec2,
+    velocity: Vec2,
+}
+
+// has to run in FixedUpdate after run_steering_agents and before integrate_kinetic_entities,
+// mixes separation, alignment and cohesion on top of whatever the member's own behavior wants
+pub fn run_flocking(
+    flocking_settings: Res<FlockingSettings>,
+    steering_settings: Res<SteeringBehaviorSettings>,
+    collision_map: Option<Res<TileCollisionMap>>,
+    mut grid: ResMut<SpatialGrid>,
+    mut query: Query<
+        (
+            Entity,
+            &KineticEntityComponents,
+            &FlockMember,
+            &mut SteeringInput,
+        ),
+        With<EnvironmentEntityTag>,
+    >,
+) {
+    if grid.cell_size != flocking_settings.neighbour_radius {
+        *grid = SpatialGrid::new(flocking_settings.neighbour_radius);
+    }
+    grid.clear();
+
+    let boids: Vec<Boid> = query
+        .iter()
+        .map(|(entity, kinetics, member, _)| Boid {
+            entity,
+            flock: member.flock,
+            position: kinetics.position.truncate(),
+            velocity: Vec2::new(kinetics.x_axis_displacement, kinetics.y_axis_displacement),
+        })
+        .collect();
+    for (index, boid) in boids.iter().enumerate() {
+        grid.insert(index, boid.position);
+    }
+
+    for (index, boid) in boids.iter().enumerate() {
+        let mut separation = Vec2::ZERO;
+        let mut position_sum = Vec2::ZERO;
+        let mut velocity_sum = Vec2::ZERO;
+        let mut neighbours = 0;
+
+        for other_index in grid.nearby(boid.position) {
+            let other = &boids[other_index];
+            if other_index == index || other.flock != boid.flock {
+                continue;
+            }
+            let offset = boid.position - other.position;
+            let distance = offset.length();
+            if distance >= flocking_settings.neighbour_radius {
+                continue;
+            }
+            neighbours += 1;
+            position_sum += other.position;
+            velocity_sum += other.velocity;
+            // the closer the neighbour, the harder the push
+            if distance > f32::EPSILON && distance < flocking_settings.separation_radius {
+                separation +=
+                    offset / distance * (1.0 - distance / flocking_settings.separation_radius);
+            }
+        }
+
+        let Ok((_, kinetics, _, mut steering_input)) = query.get_mut(boid.entity) else {
+            continue;
+        };
+
+        let mut steer = steering_input.0 * flocking_settings.behavior_weight;
+        if neighbours > 0 {
+            let center = position_sum / neighbours as f32;
+            let cohesion = (center - boid.position).normalize_or_zero();
+            let alignment = velocity_sum.normalize_or_zero();
+            steer += separation.clamp_length_max(1.0) * flocking_settings.separation_weight
+                + alignment * flocking_settings.alignment_weight
+                + cohesion * flocking_settings.cohesion_weight;
+        }
+
+        steering_input.0 = match &collision_map {
+            Some(collision_map) => avoid_walls(
+                collision_map,
+                kinetics.position,
+                steer,
+                steering_settings.collision_lookahead,
+            ),
+            None => steer,
+        };
+    }
+}
@@ -0,0 +1,6 @@
+pub mod ai_components;
+pub mod ai_res;
+pub mod ai_sys;
+pub mod flock_sys;
+pub mod path_res;
+pub mod path_sys;
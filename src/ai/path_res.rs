@@ -0,0 +1,285 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, VecDeque},
+    f32::consts::SQRT_2,
+};
+
+use bevy::{
+    prelude::{Entity, Resource},
+    utils::HashMap,
+};
+use bevy_ecs_tilemap::{map::TilemapSize, tiles::TilePos};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Connectivity {
+    // up, down, left, right
+    Four,
+    // plus the diagonals, which can't cut past a blocked corner
+    #[default]
+    Eight,
+}
+
+// per tile cost of the surface layers' water tiles, None is impassable. kept in sync with
+// TileNavigation changes
+#[derive(Resource, Default, Debug)]
+pub struct PathfindingGrid {
+    pub size: TilemapSize,
+    costs: Vec<Option<f32>>,
+    // bumped on every change so in-flight searches know to start over
+    pub version: u64,
+}
+
+impl PathfindingGrid {
+    pub fn new(size: TilemapSize) -> Self {
+        Self {
+            size,
+            // nothing is walkable until a water tile turns up there
+            costs: vec![None; size.count()],
+            version: 0,
+        }
+    }
+
+    // grows to fit the given size, existing tiles keep their cost
+    pub fn fit(&mut self, size: TilemapSize) {
+        if size.x <= self.size.x && size.y <= self.size.y {
+            return;
+        }
+        let mut grown = Self::new(TilemapSize {
+            x: size.x.max(self.size.x),
+            y: size.y.max(self.size.y),
+        });
+        for x in 0..self.size.x {
+            for y in 0..self.size.y {
+                let tile_pos = TilePos { x, y };
+                grown.set_cost(&tile_pos, self.cost(&tile_pos));
+            }
+        }
+        grown.version = self.version + 1;
+        *self = grown;
+    }
+
+    // costs below 1 are raised to 1, otherwise the A* heuristic could overestimate
+    pub fn set_cost(&mut self, tile_pos: &TilePos, cost: Option<f32>) {
+        if tile_pos.within_map_bounds(&self.size) {
+            self.costs[tile_pos.to_index(&self.size)] = cost.map(|cost| cost.max(1.0));
+            self.version += 1;
+        }
+    }
+
+    pub fn cost(&self, tile_pos: &TilePos) -> Option<f32> {
+        if !tile_pos.within_map_bounds(&self.size) {
+            return None;
+        }
+        self.costs[tile_pos.to_index(&self.size)]
+    }
+
+    pub fn is_walkable(&self, tile_pos: &TilePos) -> bool {
+        self.cost(tile_pos).is_some()
+    }
+
+    // blocking the whole search budget is fine for one-off lookups, AI should go through PathRequest
+    pub fn find_path(
+        &self,
+        start: TilePos,
+        goal: TilePos,
+        connectivity: Connectivity,
+    ) -> Option<Vec<TilePos>> {
+        let mut search = PathSearch::new(Entity::PLACEHOLDER, start, goal, connectivity);
+        let mut budget = usize::MAX;
+        match search.step(self, &mut budget) {
+            Some(PathSearchResult::Found(path)) => Some(path),
+            _ => None,
+        }
+    }
+
+    fn neighbours(
+        &self,
+        tile_pos: TilePos,
+        connectivity: Connectivity,
+    ) -> impl Iterator<Item = (TilePos, f32)> + '_ {
+        const ORTHOGONAL: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+        const DIAGONAL: [(i32, i32); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+
+        let offset = move |dx: i32, dy: i32| {
+            let x = tile_pos.x.checked_add_signed(dx)?;
+            let y = tile_pos.y.checked_add_signed(dy)?;
+            Some(TilePos { x, y })
+        };
+        let diagonals = match connectivity {
+            Connectivity::Four => [].as_slice(),
+            Connectivity::Eight => DIAGONAL.as_slice(),
+        };
+
+        let orthogonal = ORTHOGONAL.iter().filter_map(move |&(dx, dy)| {
+            let neighbour = offset(dx, dy)?;
+            Some((neighbour, self.cost(&neighbour)?))
+        });
+        let diagonal = diagonals.iter().filter_map(move |&(dx, dy)| {
+            let neighbour = offset(dx, dy)?;
+            let cost = self.cost(&neighbour)?;
+            // no squeezing through the gap between two blocked tiles
+            if !self.is_walkable(&offset(dx, 0)?) || !self.is_walkable(&offset(0, dy)?) {
+                return None;
+            }
+            Some((neighbour, cost * SQRT_2))
+        });
+        orthogonal.chain(diagonal)
+    }
+}
+
+pub enum PathSearchResult {
+    // start to goal, both included
+    Found(Vec<TilePos>),
+    NotFound,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct OpenTile {
+    estimate: f32,
+    tile_pos: TilePos,
+}
+
+impl PartialEq for OpenTile {
+    fn eq(&self, other: &Self) -> bool {
+        self.estimate.total_cmp(&other.estimate) == Ordering::Equal
+    }
+}
+
+impl Eq for OpenTile {}
+
+impl PartialOrd for OpenTile {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// reversed so the BinaryHeap pops the lowest estimate first
+impl Ord for OpenTile {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.total_cmp(&self.estimate)
+    }
+}
+
+// an A* search that can be run a few tiles at a time
+#[derive(Debug)]
+pub struct PathSearch {
+    pub entity: Entity,
+    pub start: TilePos,
+    pub goal: TilePos,
+    pub connectivity: Connectivity,
+    grid_version: u64,
+    open: BinaryHeap<OpenTile>,
+    cost_so_far: HashMap<TilePos, f32>,
+    came_from: HashMap<TilePos, TilePos>,
+}
+
+impl PathSearch {
+    pub fn new(entity: Entity, start: TilePos, goal: TilePos, connectivity: Connectivity) -> Self {
+        let mut search = Self {
+            entity,
+            start,
+            goal,
+            connectivity,
+            grid_version: 0,
+            open: BinaryHeap::new(),
+            cost_so_far: HashMap::default(),
+            came_from: HashMap::default(),
+        };
+        search.restart(0);
+        search
+    }
+
+    fn restart(&mut self, grid_version: u64) {
+        self.grid_version = grid_version;
+        self.open.clear();
+        self.cost_so_far.clear();
+        self.came_from.clear();
+        self.open.push(OpenTile {
+            estimate: self.heuristic(self.start),
+            tile_pos: self.start,
+        });
+        self.cost_so_far.insert(self.start, 0.0);
+    }
+
+    // expands tiles until the search finishes or the budget runs out, None means not done yet
+    pub fn step(&mut self, grid: &PathfindingGrid, budget: &mut usize) -> Option<PathSearchResult> {
+        if self.grid_version != grid.version {
+            self.restart(grid.version);
+        }
+        if !grid.is_walkable(&self.goal) {
+            return Some(PathSearchResult::NotFound);
+        }
+
+        while *budget > 0 {
+            let Some(current) = self.open.pop() else {
+                return Some(PathSearchResult::NotFound);
+            };
+            *budget -= 1;
+
+            if current.tile_pos == self.goal {
+                return Some(PathSearchResult::Found(self.reconstruct_path()));
+            }
+
+            let current_cost = self.cost_so_far[&current.tile_pos];
+            for (neighbour, step_cost) in grid.neighbours(current.tile_pos, self.connectivity) {
+                let cost = current_cost + step_cost;
+                if self
+                    .cost_so_far
+                    .get(&neighbour)
+                    .is_some_and(|&known| known <= cost)
+                {
+                    continue;
+                }
+                self.cost_so_far.insert(neighbour, cost);
+                self.came_from.insert(neighbour, current.tile_pos);
+                self.open.push(OpenTile {
+                    estimate: cost + self.heuristic(neighbour),
+                    tile_pos: neighbour,
+                });
+            }
+        }
+        None
+    }
+
+    // manhattan for 4-connectivity, octile for 8
+    fn heuristic(&self, tile_pos: TilePos) -> f32 {
+        let dx = tile_pos.x.abs_diff(self.goal.x) as f32;
+        let dy = tile_pos.y.abs_diff(self.goal.y) as f32;
+        match self.connectivity {
+            Connectivity::Four => dx + dy,
+            Connectivity::Eight => dx.max(dy) + (SQRT_2 - 1.0) * dx.min(dy),
+        }
+    }
+
+    fn reconstruct_path(&self) -> Vec<TilePos> {
+        let mut path = vec![self.goal];
+        let mut current = self.goal;
+        while let Some(&previous) = self.came_from.get(&current) {
+            path.push(previous);
+            current = previous;
+        }
+        path.reverse();
+        path
+    }
+}
+
+// searches waiting for their share of the per tick budget, oldest first
+#[derive(Resource, Default, Debug)]
+pub struct PathfindingQueue {
+    pub searches: VecDeque<PathSearch>,
+}
+
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct PathfindingSettings {
+    // how many tiles all searches together may expand per fixed tick
+    pub expansions_per_tick: usize,
+}
+
+impl Default for PathfindingSettings {
+    fn default() -> Self {
+        Self {
+            expansions_per_tick: 256,
+        }
+    }
+}
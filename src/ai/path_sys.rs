@@ -0,0 +1,222 @@
+use bevy::{
+    math::Vec2,
+    prelude::{Changed, Commands, Entity, Has, Query, Res, ResMut, With, Without},
+};
+use bevy_ecs_tilemap::{
+    map::{TilemapId, TilemapSize},
+    tiles::TilePos,
+};
+
+use crate::{
+    ai::{
+        ai_components::{FollowPath, PathNotFound, PathRequest, SteeringAgent, SteeringBehavior},
+        ai_res::SteeringBehaviorSettings,
+        path_res::{
+            Connectivity, PathSearch, PathSearchResult, PathfindingGrid, PathfindingQueue,
+            PathfindingSettings,
+        },
+    },
+    kinetic_components::{KineticEntityComponents, SteeringInput},
+    map::{
+        tile_overlap_sys::{calc_checked_tile_pos, tile_world_position},
+        tiled_components::{TileNavigation, WaterTileTag},
+        tiled_res::TileCollisionMap,
+    },
+};
+
+// tile navigation changes (the tiles spawning included) feed into the grid and the collision map,
+// only water tiles can be swum through no matter what their navigation says
+pub fn update_pathfinding_grid(
+    mut commands: Commands,
+    mut grid: ResMut<PathfindingGrid>,
+    mut collision_map: Option<ResMut<TileCollisionMap>>,
+    tilemap_query: Query<&TilemapSize>,
+    tile_query: Query<
+        (&TilePos, &TilemapId, &TileNavigation, Has<WaterTileTag>),
+        Changed<TileNavigation>,
+    >,
+    path_query: Query<(Entity, &FollowPath)>,
+) {
+    if tile_query.is_empty() {
+        return;
+    }
+
+    let mut blocked = Vec::new();
+    for (tile_pos, tilemap_id, navigation, water) in tile_query.iter() {
+        if let Ok(tilemap_size) = tilemap_query.get(tilemap_id.0) {
+            grid.fit(*tilemap_size);
+        }
+        let cost = navigation.cost.filter(|_| water);
+        grid.set_cost(tile_pos, cost);
+        if let Some(collision_map) = collision_map.as_mut() {
+            collision_map.set_solid(tile_pos, cost.is_none());
+        }
+        if cost.is_none() {
+            blocked.push(*tile_pos);
+        }
+    }
+
+    // routes through a tile that just got blocked get asked for again
+    for (entity, path) in path_query.iter() {
+        let Some(&goal) = path.tiles.last() else {
+            continue;
+        };
+        if path.tiles[path.next..]
+            .iter()
+            .any(|tile_pos| blocked.contains(tile_pos))
+        {
+            commands
+                .entity(entity)
+                .remove::<FollowPath>()
+                .insert(PathRequest {
+                    goal,
+                    connectivity: path.connectivity,
+                });
+        }
+    }
+}
+
+// what request_seek_paths needs to tell whether a seeker still has to ask for a route
+type SeekPathData = (
+    Entity,
+    &'static KineticEntityComponents,
+    &'static SteeringAgent,
+    Option<&'static FollowPath>,
+    Option<&'static PathNotFound>,
+);
+
+// seekers get routed around whatever is between them and their target, plain seeking takes over
+// again inside the target's tile or when there is no route to it at all
+pub fn request_seek_paths(
+    mut commands: Commands,
+    query: Query<SeekPathData, Without<PathRequest>>,
+) {
+    for (entity, kinetics, agent, path, not_found) in query.iter() {
+        let SteeringBehavior::Seek { target } = agent.behavior else {
+            continue;
+        };
+        let Some(goal) = calc_checked_tile_pos(&target.extend(0.0)) else {
+            continue;
+        };
+        let already_there = calc_checked_tile_pos(&kinetics.position) == Some(goal);
+        let on_the_way = path.is_some_and(|path| path.tiles.last() == Some(&goal));
+        let gave_up = not_found.is_some_and(|not_found| not_found.goal == goal);
+        if already_there || on_the_way || gave_up {
+            continue;
+        }
+        commands.entity(entity).insert(PathRequest {
+            goal,
+            connectivity: Connectivity::default(),
+        });
+    }
+}
+
+// a new request replaces whatever the entity was still waiting on or following
+pub fn queue_path_requests(
+    mut commands: Commands,
+    mut queue: ResMut<PathfindingQueue>,
+    query: Query<(Entity, &KineticEntityComponents, &PathRequest), Changed<PathRequest>>,
+) {
+    for (entity, kinetics, request) in query.iter() {
+        queue.searches.retain(|search| search.entity != entity);
+        commands
+            .entity(entity)
+            .remove::<(FollowPath, PathNotFound)>();
+
+        let Some(start) = calc_checked_tile_pos(&kinetics.position) else {
+            commands
+                .entity(entity)
+                .remove::<PathRequest>()
+                .insert(PathNotFound { goal: request.goal });
+            continue;
+        };
+        queue.searches.push_back(PathSearch::new(
+            entity,
+            start,
+            request.goal,
+            request.connectivity,
+        ));
+    }
+}
+
+// works through the queue oldest first until the tick's expansion budget is used up,
+// so a burst of requests gets spread over several ticks instead of stalling one
+pub fn run_path_searches(
+    mut commands: Commands,
+    settings: Res<PathfindingSettings>,
+    grid: Res<PathfindingGrid>,
+    mut queue: ResMut<PathfindingQueue>,
+    request_query: Query<(), With<PathRequest>>,
+) {
+    let mut budget = settings.expansions_per_tick;
+    while budget > 0 {
+        let Some(entity) = queue.searches.front().map(|search| search.entity) else {
+            break;
+        };
+        // despawned or cancelled since it was queued
+        if request_query.get(entity).is_err() {
+            queue.searches.pop_front();
+            continue;
+        }
+        let Some(result) = queue
+            .searches
+            .front_mut()
+            .and_then(|search| search.step(&grid, &mut budget))
+        else {
+            break;
+        };
+        let Some(search) = queue.searches.pop_front() else {
+            break;
+        };
+
+        let mut entity_commands = commands.entity(search.entity);
+        entity_commands.remove::<PathRequest>();
+        match result {
+            PathSearchResult::Found(tiles) => {
+                // the first tile is the one the entity is already on
+                let next = usize::from(tiles.len() > 1);
+                entity_commands.insert(FollowPath {
+                    tiles,
+                    next,
+                    connectivity: search.connectivity,
+                });
+            }
+            PathSearchResult::NotFound => {
+                entity_commands.insert(PathNotFound { goal: search.goal });
+            }
+        }
+    }
+}
+
+// has to run in FixedUpdate after the steering behaviors and before integrate_kinetic_entities,
+// a path being followed wins over whatever they wanted
+pub fn follow_paths(
+    mut commands: Commands,
+    settings: Res<SteeringBehaviorSettings>,
+    mut query: Query<(
+        Entity,
+        &KineticEntityComponents,
+        &mut FollowPath,
+        &mut SteeringInput,
+    )>,
+) {
+    for (entity, kinetics, mut path, mut steering_input) in query.iter_mut() {
+        let position = kinetics.position.truncate();
+        while let Some(tile_pos) = path.tiles.get(path.next) {
+            if tile_world_position(tile_pos).distance(position) > settings.arrive_radius {
+                break;
+            }
+            path.next += 1;
+        }
+
+        match path.tiles.get(path.next) {
+            Some(tile_pos) => {
+                steering_input.0 = (tile_world_position(tile_pos) - position).normalize_or_zero();
+            }
+            None => {
+                steering_input.0 = Vec2::ZERO;
+                commands.entity(entity).remove::<FollowPath>();
+            }
+        }
+    }
+}
@@ -1,4 +1,13 @@
-use bevy::prelude::{Component, Deref, DerefMut, Timer};
+use bevy::{
+    asset::Handle,
+    math::Vec2,
+    prelude::{Component, Deref, DerefMut, Entity, Image, TextureAtlasLayout, Timer, TimerMode},
+};
+
+use crate::{
+    WAKE_TRAIL_EMIT_INTERVAL, WAKE_TRAIL_END_SCALE, WAKE_TRAIL_LIFETIME, WAKE_TRAIL_MIN_SPEED,
+    WAKE_TRAIL_START_SCALE,
+};
 
 #[derive(Component, Clone, Copy)]
 pub struct AnimationComponent {
@@ -10,5 +19,92 @@ pub struct AnimationComponent {
 #[derive(Component, Deref, DerefMut)]
 pub struct AnimationTimer(pub Timer);
 
+#[derive(Clone, Debug)]
+pub struct FrameMarker {
+    // counted from the clip's start_idx, not the raw atlas index
+    pub frame: u32,
+    pub name: String,
+}
+
+#[derive(Component, Clone, Debug, Default)]
+pub struct AnimationFrameMarkers(pub Vec<FrameMarker>);
+
 #[derive(Component)]
 pub struct OverlayAnimationTag;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverlayCondition {
+    WhileMoving,
+    OnWaterTile,
+    AtSurface,
+    Submerged,
+}
+
+// describes one animated sprite that should ride along with whatever entity holds it
+#[derive(Clone)]
+pub struct OverlayAttachment {
+    pub name: String,
+    pub texture: Handle<Image>,
+    pub layout: Handle<TextureAtlasLayout>,
+    pub animation: AnimationComponent,
+    pub offset: Vec2,
+    pub z_level: f32,
+    pub follow_rotation: bool,
+    // all of these have to hold for the overlay to be shown
+    pub conditions: Vec<OverlayCondition>,
+}
+
+#[derive(Component, Clone, Default)]
+pub struct OverlayAttachments(pub Vec<OverlayAttachment>);
+
+// lives on the spawned overlay sprite, pointing back at the entity it follows
+#[derive(Component)]
+pub struct AttachedOverlay {
+    pub target: Entity,
+    pub offset: Vec2,
+    pub z_level: f32,
+    pub follow_rotation: bool,
+    pub conditions: Vec<OverlayCondition>,
+}
+
+// a water tile playing its ripple clip through to the end, independent of who touched it
+#[derive(Component, Clone, Copy)]
+pub struct TileRipple {
+    pub strength: f32,
+}
+
+// a ripple that was spread from a neighbouring tile and starts once the delay runs out
+#[derive(Component)]
+pub struct PendingTileRipple {
+    pub delay: Timer,
+    pub strength: f32,
+}
+
+// drops wake trail sprites behind an entity while it is moving
+#[derive(Component)]
+pub struct WakeEmitter {
+    pub interval: Timer,
+    pub lifetime: f32,
+    pub min_speed: f32,
+    pub start_scale: f32,
+    pub end_scale: f32,
+}
+
+impl Default for WakeEmitter {
+    fn default() -> Self {
+        Self {
+            interval: Timer::from_seconds(WAKE_TRAIL_EMIT_INTERVAL, TimerMode::Repeating),
+            lifetime: WAKE_TRAIL_LIFETIME,
+            min_speed: WAKE_TRAIL_MIN_SPEED,
+            start_scale: WAKE_TRAIL_START_SCALE,
+            end_scale: WAKE_TRAIL_END_SCALE,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct WakeTrailSprite {
+    pub lifetime: Timer,
+    pub start_scale: f32,
+    pub end_scale: f32,
+}
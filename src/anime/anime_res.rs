@@ -1,6 +1,6 @@
 use bevy::{
     asset::Handle,
-    prelude::{Image, Resource},
+    prelude::{Entity, Image, Resource, TextureAtlasLayout},
 };
 use bevy_asset_loader::asset_collection::AssetCollection;
 
@@ -9,6 +9,9 @@ pub struct OverlayAnimationAssets {
     #[asset(path = "sprite_data/random_test_animations.png")]
     //WAKE_ANIMATION_FILE_PATH
     pub animation_image_handle: Handle<Image>,
+    #[asset(path = "sprite_data/bubbles.png")]
+    //BUBBLE_ANIMATION_FILE_PATH
+    pub bubble_image_handle: Handle<Image>,
 }
 
 #[derive(AssetCollection, Resource)]
@@ -23,3 +26,33 @@ pub struct PlayerEntityAnimationAssets {
     // PLAYER_ENTITY_TEXTURE_FILE_PATH
     pub image_handle: Handle<Image>,
 }
+
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct RippleSettings {
+    // seconds before a ripple hops over to the neighbouring tiles
+    pub spread_delay: f32,
+    // strength multiplier applied on every hop
+    pub falloff: f32,
+    // ripples weaker than this die out instead of spreading
+    pub min_strength: f32,
+    pub include_diagonals: bool,
+}
+
+impl Default for RippleSettings {
+    fn default() -> Self {
+        Self {
+            spread_delay: 0.15,
+            falloff: 0.6,
+            min_strength: 0.2,
+            include_diagonals: false,
+        }
+    }
+}
+
+// trail sprites that finished fading get hidden and parked here instead of despawned
+#[derive(Resource)]
+pub struct WakeTrailPool {
+    pub texture: Handle<Image>,
+    pub layout: Handle<TextureAtlasLayout>,
+    pub free: Vec<Entity>,
+}
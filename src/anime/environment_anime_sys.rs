@@ -1,19 +1,17 @@
-use bevy::prelude::{Query, Res, TextureAtlas, Time, With};
+use bevy::prelude::{EventWriter, Query, Res, TextureAtlas, Time, With};
 
 use crate::{
-    anime::anime_components::{AnimationComponent, AnimationTimer},
+    anime::frame_event_sys::{send_frame_events, AnimationFrameEvent, FrameAnimationData},
     kinetic_components::EnvironmentEntityTag,
 };
 
 pub fn animate_env_entity_animations(
     time: Res<Time>,
-    mut query: Query<
-        (&mut AnimationTimer, &AnimationComponent, &mut TextureAtlas),
-        With<EnvironmentEntityTag>,
-    >,
+    mut frame_event_writer: EventWriter<AnimationFrameEvent>,
+    mut query: Query<FrameAnimationData<TextureAtlas>, With<EnvironmentEntityTag>>,
 ) {
     //TODO: refactor all the animation logic to work without having copy pasted code
-    for (mut timer, animation, mut texture_atlas) in query.iter_mut() {
+    for (entity, mut timer, animation, mut texture_atlas, frame_markers) in query.iter_mut() {
         timer.0.tick(time.delta());
 
         if timer.0.just_finished() {
@@ -26,6 +24,13 @@ pub fn animate_env_entity_animations(
             };
 
             texture_atlas.index = next_index;
+            send_frame_events(
+                entity,
+                animation,
+                frame_markers,
+                next_index as u32,
+                &mut frame_event_writer,
+            );
         }
     }
 }
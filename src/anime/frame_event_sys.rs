@@ -0,0 +1,41 @@
+use bevy::prelude::{Entity, Event, EventWriter};
+
+use crate::anime::anime_components::{AnimationComponent, AnimationFrameMarkers, AnimationTimer};
+
+// everything an animation system needs to step a clip and report its marked frames, where
+// `Frame` is whatever holds the shown index (a `TextureAtlas` or a `TileTextureIndex`)
+pub type FrameAnimationData<Frame> = (
+    Entity,
+    &'static mut AnimationTimer,
+    &'static AnimationComponent,
+    &'static mut Frame,
+    Option<&'static AnimationFrameMarkers>,
+);
+
+#[derive(Event, Debug, Clone)]
+pub struct AnimationFrameEvent {
+    pub entity: Entity,
+    pub name: String,
+}
+
+// called by the animation systems right after they switch to a new frame
+pub fn send_frame_events(
+    entity: Entity,
+    animation: &AnimationComponent,
+    frame_markers: Option<&AnimationFrameMarkers>,
+    shown_index: u32,
+    frame_event_writer: &mut EventWriter<AnimationFrameEvent>,
+) {
+    let Some(frame_markers) = frame_markers else {
+        return;
+    };
+    let clip_frame = shown_index.saturating_sub(animation.start_idx);
+    for marker in frame_markers.0.iter() {
+        if marker.frame == clip_frame {
+            frame_event_writer.send(AnimationFrameEvent {
+                entity,
+                name: marker.name.clone(),
+            });
+        }
+    }
+}
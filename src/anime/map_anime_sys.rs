@@ -1,74 +1,170 @@
 use bevy::{
-    math::Vec3,
-    prelude::{Event, EventReader, EventWriter, Query, Res, Time, With},
+    math::Vec2,
+    prelude::{
+        Added, Commands, Entity, EventReader, EventWriter, Query, Res, Time, Timer, TimerMode,
+        With, Without,
+    },
+};
+use bevy_ecs_tilemap::{
+    helpers::square_grid::neighbors::Neighbors,
+    map::TilemapId,
+    prelude::{TilePos, TileStorage, TileTextureIndex},
 };
-use bevy_ecs_tilemap::{prelude::TileTextureIndex, tiles::TilePos};
 use tracy_client::span;
 
 use crate::{
-    anime::anime_components::{AnimationComponent, AnimationTimer},
-    kinetic_components::{KineticEntityComponents, PlayerEntityTag},
-    map::tiled_components::TileEntityTag,
-    TILE_SIZE,
+    anime::{
+        anime_components::{AnimationComponent, PendingTileRipple, TileRipple},
+        anime_res::RippleSettings,
+        frame_event_sys::{send_frame_events, AnimationFrameEvent, FrameAnimationData},
+    },
+    environment::weather::Weather,
+    kinetic_components::{DepthComponent, KineticEntityComponents, PlayerEntityTag},
+    map::{
+        tile_overlap_sys::{TileEntered, TileStay},
+        tiled_components::TileEntityTag,
+    },
+    DEFAULT_SPEED,
 };
 
-#[derive(Event)]
-pub struct TileAnimationEvent {
-    pub tile_pos: TilePos,
-}
+// animated water tiles that are not already rippling
+type IdleRippleTileFilter = (
+    With<TileEntityTag>,
+    With<AnimationComponent>,
+    Without<TileRipple>,
+);
+
+// animated tiles a ripple can still spread to
+type RippleNeighbourFilter = (
+    With<AnimationComponent>,
+    Without<TileRipple>,
+    Without<PendingTileRipple>,
+);
 
-pub fn animate_overlapped_tiles_event_based(
-    mut entity_query: Query<&KineticEntityComponents, With<PlayerEntityTag>>,
-    mut overlap_event_writer: EventWriter<TileAnimationEvent>,
+pub fn handle_overlap_event(
+    mut commands: Commands,
+    mut entered_reader: EventReader<TileEntered>,
+    mut stay_reader: EventReader<TileStay>,
+    player_query: Query<(&KineticEntityComponents, Option<&DepthComponent>), With<PlayerEntityTag>>,
+    tile_query: Query<(), IdleRippleTileFilter>,
 ) {
-    let _span = span!("tile animation_loadtime event send");
-    for player_entity in entity_query.iter_mut() {
-        let current_tile_pos = calc_tile_pos(&player_entity.position);
-        overlap_event_writer.send(TileAnimationEvent {
-            tile_pos: current_tile_pos,
-        });
+    let _span = span!("tile animation_loadtime event read");
+
+    let overlapped_tiles = entered_reader
+        .read()
+        .map(|event| (event.entity, event.tile))
+        .chain(stay_reader.read().map(|event| (event.entity, event.tile)));
+
+    for (entity, tile) in overlapped_tiles {
+        // TODO: only the player stirs up the water for now
+        let Ok((player_kinetics, depth)) = player_query.get(entity) else {
+            continue;
+        };
+        // swimming underneath the surface doesn't disturb it
+        if depth.is_some_and(|depth| !depth.at_surface()) {
+            continue;
+        }
+        if !tile_query.contains(tile) {
+            continue;
+        }
+        let speed = Vec2::new(
+            player_kinetics.x_axis_displacement,
+            player_kinetics.y_axis_displacement,
+        )
+        .length();
+        commands
+            .entity(tile)
+            .remove::<PendingTileRipple>()
+            .insert(TileRipple {
+                strength: (speed / DEFAULT_SPEED).clamp(0.0, 1.0),
+            });
     }
 }
 
-pub fn handle_overlap_event(
-    time: Res<Time>,
-    mut event_reader: EventReader<TileAnimationEvent>,
-    mut tile_query: Query<
-        (
-            &TilePos,
-            &mut AnimationTimer,
-            &AnimationComponent,
-            //TODO: at somepoint shouldnt i be able to convert all texture stuff to TextureAtlases?
-            &mut TileTextureIndex,
-        ),
-        With<TileEntityTag>,
-    >,
+pub fn spread_tile_ripples(
+    mut commands: Commands,
+    ripple_settings: Res<RippleSettings>,
+    started_ripples: Query<(&TilePos, &TilemapId, &TileRipple), Added<TileRipple>>,
+    tilemap_query: Query<&TileStorage>,
+    neighbour_query: Query<(), RippleNeighbourFilter>,
 ) {
-    let _span = span!("tile animation_loadtime event read");
+    let _span = span!("tile ripple spread");
+    for (tile_pos, tilemap_id, ripple) in started_ripples.iter() {
+        let spread_strength = ripple.strength * ripple_settings.falloff;
+        if spread_strength < ripple_settings.min_strength {
+            continue;
+        }
+        let Ok(tile_storage) = tilemap_query.get(tilemap_id.0) else {
+            continue;
+        };
+        let neighbours = Neighbors::get_square_neighboring_positions(
+            tile_pos,
+            &tile_storage.size,
+            ripple_settings.include_diagonals,
+        )
+        .entities(tile_storage);
 
-    for event in event_reader.read() {
-        for (tile_pos, mut animation_timer, animation, mut tilemap_texture_index) in
-            tile_query.iter_mut()
-        {
-            if *tile_pos == event.tile_pos {
-                animation_timer.tick(time.delta());
-                if animation_timer.just_finished() {
-                    tilemap_texture_index.0 = if tilemap_texture_index.0 == animation.end_idx {
-                        animation.start_idx
-                    } else {
-                        tilemap_texture_index.0 + 1
-                    };
-                }
+        for neighbour in neighbours.iter() {
+            if neighbour_query.contains(*neighbour) {
+                commands.entity(*neighbour).insert(PendingTileRipple {
+                    delay: Timer::from_seconds(ripple_settings.spread_delay, TimerMode::Once),
+                    strength: spread_strength,
+                });
             }
         }
     }
 }
 
-fn calc_tile_pos(translation: &Vec3) -> TilePos {
-    let tile_x = ((translation.x + (TILE_SIZE / 2.0)) / TILE_SIZE).floor();
-    let tile_y = ((translation.y + (TILE_SIZE / 2.0)) / TILE_SIZE).floor();
-    TilePos {
-        x: tile_x as u32,
-        y: tile_y as u32,
+pub fn start_pending_tile_ripples(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut PendingTileRipple)>,
+) {
+    for (entity, mut pending_ripple) in query.iter_mut() {
+        pending_ripple.delay.tick(time.delta());
+        if pending_ripple.delay.finished() {
+            commands
+                .entity(entity)
+                .remove::<PendingTileRipple>()
+                .insert(TileRipple {
+                    strength: pending_ripple.strength,
+                });
+        }
+    }
+}
+
+pub fn animate_tile_ripples(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut frame_event_writer: EventWriter<AnimationFrameEvent>,
+    weather: Option<Res<Weather>>,
+    //TODO: at somepoint shouldnt i be able to convert all texture stuff to TextureAtlases?
+    mut tile_query: Query<FrameAnimationData<TileTextureIndex>, With<TileRipple>>,
+) {
+    let _span = span!("tile ripple animation");
+    // windier weather churns the water faster
+    let delta = time
+        .delta()
+        .mul_f32(weather.map_or(1.0, |weather| weather.water_animation_speed()));
+    for (entity, mut animation_timer, animation, mut tilemap_texture_index, frame_markers) in
+        tile_query.iter_mut()
+    {
+        animation_timer.tick(delta);
+        if animation_timer.just_finished() {
+            if tilemap_texture_index.0 == animation.end_idx {
+                // clip played through, rest on the first frame until something disturbs it again
+                tilemap_texture_index.0 = animation.start_idx;
+                commands.entity(entity).remove::<TileRipple>();
+            } else {
+                tilemap_texture_index.0 += 1;
+            }
+            send_frame_events(
+                entity,
+                animation,
+                frame_markers,
+                tilemap_texture_index.0,
+                &mut frame_event_writer,
+            );
+        }
     }
 }
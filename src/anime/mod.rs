@@ -1,5 +1,7 @@
 pub mod anime_components;
 pub mod anime_res;
 pub mod environment_anime_sys;
+pub mod frame_event_sys;
 pub mod map_anime_sys;
 pub mod overlay_anime_sys;
+pub mod wake_sys;
@@ -1,26 +1,39 @@
 use bevy::{
     asset::Assets,
     core::Name,
-    hierarchy::BuildChildren,
-    math::UVec2,
+    math::{Quat, UVec2, Vec2},
     prelude::{
-        Commands, Entity, Query, Res, ResMut, TextureAtlas, TextureAtlasLayout, Time, Timer,
-        TimerMode, With,
+        Added, Commands, Entity, EventWriter, Query, Res, ResMut, TextureAtlas, TextureAtlasLayout,
+        Time, Timer, TimerMode, Transform, Visibility, With, Without,
     },
     sprite::SpriteBundle,
 };
 
 use crate::{
     anime::{
-        anime_components::{AnimationComponent, AnimationTimer, OverlayAnimationTag},
+        anime_components::{
+            AnimationComponent, AnimationTimer, AttachedOverlay, OverlayAnimationTag,
+            OverlayAttachment, OverlayAttachments, OverlayCondition,
+        },
         anime_res::OverlayAnimationAssets,
+        frame_event_sys::{send_frame_events, AnimationFrameEvent, FrameAnimationData},
     },
-    kinetic_components::PlayerEntityTag,
-    TILE_SIZE, WAKE_ANIMATION_SPEED, WAKE_ANIMATION_TEXTURE_COLUMN_LENGTH,
-    WAKE_ANIMATION_TEXTURE_END_IDX, WAKE_ANIMATION_TEXTURE_ROW_LENGTH,
-    WAKE_ANIMATION_TEXTURE_START_IDX,
+    kinetic_components::{DepthComponent, KineticEntityComponents, PlayerEntityTag},
+    map::tiled_components::{TileOccupancy, WaterTileTag},
+    BUBBLE_ANIMATION_SPEED, BUBBLE_ANIMATION_TEXTURE_COLUMN_LENGTH,
+    BUBBLE_ANIMATION_TEXTURE_END_IDX, BUBBLE_ANIMATION_TEXTURE_ROW_LENGTH,
+    BUBBLE_ANIMATION_TEXTURE_START_IDX, OVERLAY_ANIMATIONS_Z_LEVEL, OVERLAY_MOVING_MIN_SPEED,
+    TILE_SIZE,
 };
 
+// what the overlay conditions look at on the entity an overlay is attached to
+type OverlayTargetData = (
+    &'static Transform,
+    Option<&'static KineticEntityComponents>,
+    Option<&'static TileOccupancy>,
+    Option<&'static DepthComponent>,
+);
+
 pub fn attach_overlay_animation_to_player_entity(
     mut commands: Commands,
     overlay_animation_assets: Res<OverlayAnimationAssets>,
@@ -28,51 +41,123 @@ pub fn attach_overlay_animation_to_player_entity(
     query: Query<Entity, With<PlayerEntityTag>>,
 ) {
     for entity in query.iter() {
-        let overlay_animation_texture_atlas_layout =
-            texture_atlas_layouts.add(TextureAtlasLayout::from_grid(
-                UVec2::splat(TILE_SIZE as u32),
-                WAKE_ANIMATION_TEXTURE_COLUMN_LENGTH,
-                WAKE_ANIMATION_TEXTURE_ROW_LENGTH,
-                None,
-                None,
-            ));
+        let bubble_texture_atlas_layout = texture_atlas_layouts.add(TextureAtlasLayout::from_grid(
+            UVec2::splat(TILE_SIZE as u32),
+            BUBBLE_ANIMATION_TEXTURE_COLUMN_LENGTH,
+            BUBBLE_ANIMATION_TEXTURE_ROW_LENGTH,
+            None,
+            None,
+        ));
 
-        let animation_component = AnimationComponent {
-            start_idx: WAKE_ANIMATION_TEXTURE_START_IDX,
-            end_idx: WAKE_ANIMATION_TEXTURE_END_IDX,
-            speed: WAKE_ANIMATION_SPEED,
-        };
+        // the wake is WakeEmitter's job, the overlay is the air trickling out of the blowhole
+        // while the player is under. the bubbles rise straight up no matter the heading
+        commands
+            .entity(entity)
+            .insert(OverlayAttachments(vec![OverlayAttachment {
+                name: "PlayerEntityBubbleOverlay".to_string(),
+                texture: overlay_animation_assets.bubble_image_handle.clone(),
+                layout: bubble_texture_atlas_layout,
+                animation: AnimationComponent {
+                    start_idx: BUBBLE_ANIMATION_TEXTURE_START_IDX,
+                    end_idx: BUBBLE_ANIMATION_TEXTURE_END_IDX,
+                    speed: BUBBLE_ANIMATION_SPEED,
+                },
+                offset: Vec2::new(0.0, TILE_SIZE / 2.0),
+                z_level: OVERLAY_ANIMATIONS_Z_LEVEL,
+                follow_rotation: false,
+                conditions: vec![OverlayCondition::Submerged],
+            }]));
+    }
+}
 
-        commands.entity(entity).with_children(|parent| {
-            parent
+pub fn spawn_overlay_attachments(
+    mut commands: Commands,
+    query: Query<(Entity, &OverlayAttachments), Added<OverlayAttachments>>,
+) {
+    for (target, overlay_attachments) in query.iter() {
+        for overlay in overlay_attachments.0.iter() {
+            // overlays are not parented on purpose so the offset, z level and rotation are
+            // entirely under our control instead of inherited from the target
+            commands
                 .spawn(SpriteBundle {
-                    texture: overlay_animation_assets.animation_image_handle.clone(),
-                    transform: Default::default(), // gets overwritten by the parent??
+                    texture: overlay.texture.clone(),
+                    visibility: Visibility::Hidden,
                     ..Default::default()
                 })
                 .insert(TextureAtlas {
-                    layout: overlay_animation_texture_atlas_layout,
-                    index: WAKE_ANIMATION_TEXTURE_START_IDX as usize,
+                    layout: overlay.layout.clone(),
+                    index: overlay.animation.start_idx as usize,
                 })
-                .insert(animation_component)
+                .insert(overlay.animation)
                 .insert(AnimationTimer(Timer::from_seconds(
-                    animation_component.speed,
+                    overlay.animation.speed,
                     TimerMode::Repeating,
                 )))
-                .insert(Name::new("PlayerEntityOverlayAnimation"))
+                .insert(AttachedOverlay {
+                    target,
+                    offset: overlay.offset,
+                    z_level: overlay.z_level,
+                    follow_rotation: overlay.follow_rotation,
+                    conditions: overlay.conditions.clone(),
+                })
+                .insert(Name::new(overlay.name.clone()))
                 .insert(OverlayAnimationTag);
+        }
+    }
+}
+
+pub fn sync_attached_overlays(
+    mut commands: Commands,
+    target_query: Query<OverlayTargetData, Without<AttachedOverlay>>,
+    water_tile_query: Query<(), With<WaterTileTag>>,
+    mut overlay_query: Query<(Entity, &AttachedOverlay, &mut Transform, &mut Visibility)>,
+) {
+    for (overlay_entity, overlay, mut overlay_transform, mut visibility) in overlay_query.iter_mut()
+    {
+        let Ok((target_transform, kinetics, occupancy, depth)) = target_query.get(overlay.target)
+        else {
+            commands.entity(overlay_entity).despawn();
+            continue;
+        };
+
+        let rotation = if overlay.follow_rotation {
+            target_transform.rotation
+        } else {
+            Quat::IDENTITY
+        };
+        let offset = rotation * overlay.offset.extend(0.0);
+        overlay_transform.translation = target_transform.translation + offset;
+        overlay_transform.translation.z = overlay.z_level;
+        overlay_transform.rotation = rotation;
+
+        let shown = overlay.conditions.iter().all(|condition| match condition {
+            OverlayCondition::WhileMoving => kinetics.is_some_and(|kinetics| {
+                Vec2::new(kinetics.x_axis_displacement, kinetics.y_axis_displacement).length()
+                    >= OVERLAY_MOVING_MIN_SPEED
+            }),
+            OverlayCondition::OnWaterTile => occupancy.is_some_and(|occupancy| {
+                occupancy
+                    .tiles
+                    .iter()
+                    .any(|occupied| water_tile_query.contains(occupied.tile))
+            }),
+            OverlayCondition::AtSurface => depth.is_none_or(|depth| depth.at_surface()),
+            OverlayCondition::Submerged => depth.is_some_and(|depth| !depth.at_surface()),
         });
+        *visibility = if shown {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
     }
 }
 
 pub fn animate_overlay_animations(
     time: Res<Time>,
-    mut query: Query<
-        (&mut AnimationTimer, &AnimationComponent, &mut TextureAtlas),
-        With<OverlayAnimationTag>,
-    >,
+    mut frame_event_writer: EventWriter<AnimationFrameEvent>,
+    mut query: Query<FrameAnimationData<TextureAtlas>, With<OverlayAnimationTag>>,
 ) {
-    for (mut timer, animation, mut overlay_atlas) in query.iter_mut() {
+    for (entity, mut timer, animation, mut overlay_atlas, frame_markers) in query.iter_mut() {
         timer.0.tick(time.delta());
 
         if timer.0.just_finished() {
@@ -85,6 +170,13 @@ pub fn animate_overlay_animations(
             };
 
             overlay_atlas.index = next_index;
+            send_frame_events(
+                entity,
+                animation,
+                frame_markers,
+                next_index as u32,
+                &mut frame_event_writer,
+            );
         }
     }
 }
@@ -0,0 +1,148 @@
+use bevy::{
+    asset::Assets,
+    color::Color,
+    core::Name,
+    math::{Quat, UVec2, Vec2, Vec3},
+    prelude::{
+        Commands, Entity, Query, Res, ResMut, Sprite, TextureAtlas, TextureAtlasLayout, Time,
+        Timer, TimerMode, Transform, Visibility,
+    },
+    sprite::SpriteBundle,
+};
+
+use crate::{
+    anime::{
+        anime_components::{WakeEmitter, WakeTrailSprite},
+        anime_res::{OverlayAnimationAssets, WakeTrailPool},
+    },
+    kinetic_components::{DepthComponent, KineticEntityComponents},
+    TILE_SIZE, WAKE_ANIMATION_TEXTURE_COLUMN_LENGTH, WAKE_ANIMATION_TEXTURE_END_IDX,
+    WAKE_ANIMATION_TEXTURE_ROW_LENGTH, WAKE_ANIMATION_TEXTURE_START_IDX, WAKE_TRAIL_Z_LEVEL,
+};
+
+pub fn setup_wake_trail_pool(
+    mut commands: Commands,
+    overlay_animation_assets: Res<OverlayAnimationAssets>,
+    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+) {
+    let wake_texture_atlas_layout = texture_atlas_layouts.add(TextureAtlasLayout::from_grid(
+        UVec2::splat(TILE_SIZE as u32),
+        WAKE_ANIMATION_TEXTURE_COLUMN_LENGTH,
+        WAKE_ANIMATION_TEXTURE_ROW_LENGTH,
+        None,
+        None,
+    ));
+
+    commands.insert_resource(WakeTrailPool {
+        texture: overlay_animation_assets.animation_image_handle.clone(),
+        layout: wake_texture_atlas_layout,
+        free: Vec::new(),
+    });
+}
+
+pub fn emit_wake_trails(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut wake_trail_pool: ResMut<WakeTrailPool>,
+    mut emitter_query: Query<(
+        &KineticEntityComponents,
+        &mut WakeEmitter,
+        Option<&DepthComponent>,
+    )>,
+    mut trail_query: Query<(
+        &mut WakeTrailSprite,
+        &mut Transform,
+        &mut Sprite,
+        &mut TextureAtlas,
+        &mut Visibility,
+    )>,
+) {
+    for (kinetics, mut emitter, depth) in emitter_query.iter_mut() {
+        let velocity = Vec2::new(kinetics.x_axis_displacement, kinetics.y_axis_displacement);
+        let submerged = depth.is_some_and(|depth| !depth.at_surface());
+        if velocity.length() < emitter.min_speed || submerged {
+            // standing still (or swimming under) leaves no wake, and the first trail after
+            // starting up waits a full interval
+            emitter.interval.reset();
+            continue;
+        }
+
+        emitter.interval.tick(time.delta());
+        if !emitter.interval.just_finished() {
+            continue;
+        }
+
+        let transform =
+            Transform::from_xyz(kinetics.position.x, kinetics.position.y, WAKE_TRAIL_Z_LEVEL)
+                .with_rotation(Quat::from_rotation_z(velocity.y.atan2(velocity.x)))
+                .with_scale(Vec3::splat(emitter.start_scale));
+
+        let wake_trail = WakeTrailSprite {
+            lifetime: Timer::from_seconds(emitter.lifetime, TimerMode::Once),
+            start_scale: emitter.start_scale,
+            end_scale: emitter.end_scale,
+        };
+
+        if let Some(pooled_entity) = wake_trail_pool.free.pop() {
+            if let Ok((mut trail, mut trail_transform, mut sprite, mut atlas, mut visibility)) =
+                trail_query.get_mut(pooled_entity)
+            {
+                *trail = wake_trail;
+                *trail_transform = transform;
+                sprite.color = Color::WHITE;
+                atlas.index = WAKE_ANIMATION_TEXTURE_START_IDX as usize;
+                *visibility = Visibility::Inherited;
+                continue;
+            }
+        }
+
+        commands
+            .spawn(SpriteBundle {
+                texture: wake_trail_pool.texture.clone(),
+                transform,
+                ..Default::default()
+            })
+            .insert(TextureAtlas {
+                layout: wake_trail_pool.layout.clone(),
+                index: WAKE_ANIMATION_TEXTURE_START_IDX as usize,
+            })
+            .insert(wake_trail)
+            .insert(Name::new("WakeTrailSprite"));
+    }
+}
+
+pub fn fade_wake_trails(
+    time: Res<Time>,
+    mut wake_trail_pool: ResMut<WakeTrailPool>,
+    mut trail_query: Query<(
+        Entity,
+        &mut WakeTrailSprite,
+        &mut Transform,
+        &mut Sprite,
+        &mut TextureAtlas,
+        &mut Visibility,
+    )>,
+) {
+    for (entity, mut trail, mut transform, mut sprite, mut atlas, mut visibility) in
+        trail_query.iter_mut()
+    {
+        if *visibility == Visibility::Hidden {
+            continue;
+        }
+
+        trail.lifetime.tick(time.delta());
+        let progress = trail.lifetime.fraction();
+
+        transform.scale =
+            Vec3::splat(trail.start_scale + (trail.end_scale - trail.start_scale) * progress);
+        sprite.color = Color::srgba(1.0, 1.0, 1.0, 1.0 - progress);
+        let frame_count = WAKE_ANIMATION_TEXTURE_END_IDX - WAKE_ANIMATION_TEXTURE_START_IDX;
+        atlas.index =
+            (WAKE_ANIMATION_TEXTURE_START_IDX + (frame_count as f32 * progress) as u32) as usize;
+
+        if trail.lifetime.finished() {
+            *visibility = Visibility::Hidden;
+            wake_trail_pool.free.push(entity);
+        }
+    }
+}
@@ -1,21 +1,23 @@
 use bevy::{
     asset::Handle,
     log::info,
-    math::{UVec2, Vec2},
+    math::{Rect, UVec2, Vec2},
     prelude::{
-        Assets, Camera2dBundle, Commands, OrthographicProjection, ParamSet, Query, Res, Transform,
-        With,
+        Assets, Camera2dBundle, Commands, DetectChanges, Entity, Fixed, GlobalTransform,
+        OrthographicProjection, Query, Res, ResMut, Time, Transform, With, Without,
     },
     utils::default,
 };
 use bevy_render::camera::{Camera, Viewport};
 
 use crate::{
-    camera::camera_components::BottomCameraTag,
+    camera::camera_components::{BottomCameraTag, CameraFollow, CameraHome, TopCameraTag},
     environment::moon::MoonTag,
-    kinetic_components::PlayerEntityTag,
+    kinetic_components::{KineticEntityComponents, PlayerSlot},
     map::tiled_res::{TiledMapAssets, TiledMapSource},
+    player::player_res::{CoopSettings, CoopView},
     CAMERA_SCALE_MULTIPLIER, CAMERA_Z_LEVEL, NINTENDO_DS_SCREEN_HEIGHT, NINTENDO_DS_SCREEN_WIDTH,
+    TILE_SIZE,
 };
 
 pub fn top_camera(mut commands: Commands, mut query: Query<&Transform, With<MoonTag>>) {
@@ -28,14 +30,7 @@ pub fn top_camera(mut commands: Commands, mut query: Query<&Transform, With<Moon
             ),
             camera: Camera {
                 order: 2,
-                viewport: Some(Viewport {
-                    physical_position: UVec2::ZERO, // top left
-                    physical_size: UVec2::new(
-                        (NINTENDO_DS_SCREEN_WIDTH * 2.0) as u32,
-                        NINTENDO_DS_SCREEN_HEIGHT as u32,
-                    ),
-                    ..default()
-                }),
+                viewport: Some(top_screen_viewport()),
                 ..default()
             },
             projection: OrthographicProjection {
@@ -48,24 +43,55 @@ pub fn top_camera(mut commands: Commands, mut query: Query<&Transform, With<Moon
             "Moon Position: {:?}, Camera Position: {:?}",
             moon_transform.translation, camera.transform.translation
         );
-        commands.spawn(camera);
+        commands
+            .spawn(camera)
+            .insert(TopCameraTag)
+            .insert(CameraFollow::default())
+            .insert(CameraHome(moon_transform.translation.truncate()));
     }
 }
 
 pub fn bottom_camera(mut commands: Commands) {
     let normal_transform = Transform::from_xyz(0.0, 0.0, CAMERA_Z_LEVEL);
+    let viewport = bottom_screen_viewport();
     init_camera(
         &mut commands,
-        UVec2::new(0, NINTENDO_DS_SCREEN_HEIGHT as u32),
-        UVec2::new(
-            (NINTENDO_DS_SCREEN_WIDTH * 2.0) as u32,
-            NINTENDO_DS_SCREEN_HEIGHT as u32,
-        ),
+        viewport.physical_position,
+        viewport.physical_size,
         1,
         normal_transform,
     )
 }
 
+fn top_screen_viewport() -> Viewport {
+    Viewport {
+        physical_position: UVec2::ZERO, // top left
+        physical_size: UVec2::new(
+            (NINTENDO_DS_SCREEN_WIDTH * 2.0) as u32,
+            NINTENDO_DS_SCREEN_HEIGHT as u32,
+        ),
+        ..default()
+    }
+}
+
+fn bottom_screen_viewport() -> Viewport {
+    Viewport {
+        physical_position: UVec2::new(0, NINTENDO_DS_SCREEN_HEIGHT as u32),
+        ..top_screen_viewport()
+    }
+}
+
+// both screens stacked, what the bottom camera draws into while the co-op view is merged
+fn merged_screen_viewport() -> Viewport {
+    Viewport {
+        physical_size: UVec2::new(
+            (NINTENDO_DS_SCREEN_WIDTH * 2.0) as u32,
+            (NINTENDO_DS_SCREEN_HEIGHT * 2.0) as u32,
+        ),
+        ..top_screen_viewport()
+    }
+}
+
 pub fn init_camera(
     commands: &mut Commands,
     viewport_position: UVec2,
@@ -91,56 +117,182 @@ pub fn init_camera(
             },
             ..default()
         })
-        .insert(BottomCameraTag);
+        .insert(BottomCameraTag)
+        .insert(CameraFollow::default());
+}
+
+// the bottom screen is player one's, the top screen is player two's or the moon's when there is none,
+// once the players swim close enough together the bottom screen frames both of them and
+// resize_coop_viewports stretches it over the top one
+pub fn assign_camera_targets(
+    coop_settings: Res<CoopSettings>,
+    mut coop_view: ResMut<CoopView>,
+    player_query: Query<(Entity, &PlayerSlot, &KineticEntityComponents)>,
+    mut top_camera_query: Query<&mut CameraFollow, (With<TopCameraTag>, Without<BottomCameraTag>)>,
+    mut bottom_camera_query: Query<
+        &mut CameraFollow,
+        (With<BottomCameraTag>, Without<TopCameraTag>),
+    >,
+) {
+    let mut players: Vec<(usize, Entity, Vec2)> = player_query
+        .iter()
+        .map(|(entity, slot, kinetics)| (slot.0, entity, kinetics.position.truncate()))
+        .collect();
+    players.sort_by_key(|(slot, _, _)| *slot);
+
+    let player_one = players
+        .first()
+        .map(|(_, entity, position)| (*entity, *position));
+    let player_two = players
+        .get(1)
+        .filter(|_| coop_settings.enabled)
+        .map(|(_, entity, position)| (*entity, *position));
+
+    let (bottom_targets, top_targets) = match (player_one, player_two) {
+        (Some((one, one_position)), Some((two, two_position))) => {
+            let distance = one_position.distance(two_position);
+            if coop_view.merged && distance > coop_settings.split_distance {
+                coop_view.merged = false;
+            } else if !coop_view.merged && distance < coop_settings.merge_distance {
+                coop_view.merged = true;
+            }
+            if coop_view.merged {
+                (vec![one, two], Vec::new())
+            } else {
+                (vec![one], vec![two])
+            }
+        }
+        (Some((one, _)), None) => (vec![one], Vec::new()),
+        _ => (Vec::new(), Vec::new()),
+    };
+
+    for mut follow in bottom_camera_query.iter_mut() {
+        follow.targets.clone_from(&bottom_targets);
+    }
+    for mut follow in top_camera_query.iter_mut() {
+        follow.targets.clone_from(&top_targets);
+    }
+}
+
+// the merged view takes over the whole window, the top camera sits it out until the players split
+pub fn resize_coop_viewports(
+    coop_view: Res<CoopView>,
+    mut top_camera_query: Query<&mut Camera, (With<TopCameraTag>, Without<BottomCameraTag>)>,
+    mut bottom_camera_query: Query<&mut Camera, (With<BottomCameraTag>, Without<TopCameraTag>)>,
+) {
+    if !coop_view.is_changed() {
+        return;
+    }
+
+    for mut camera in top_camera_query.iter_mut() {
+        camera.is_active = !coop_view.merged;
+    }
+    for mut camera in bottom_camera_query.iter_mut() {
+        camera.viewport = Some(if coop_view.merged {
+            merged_screen_viewport()
+        } else {
+            bottom_screen_viewport()
+        });
+    }
 }
 
+type CameraTrackData = (
+    &'static mut Transform,
+    &'static OrthographicProjection,
+    &'static CameraFollow,
+    Option<&'static CameraHome>,
+);
+
+// follows the targets' kinetics rather than their Transform, which bobs up and down with jumps
 pub fn track_camera(
+    fixed_time: Res<Time<Fixed>>,
     tiled_asset: Res<TiledMapAssets>,
     map_assets: Res<Assets<TiledMapSource>>,
-    mut param_set: ParamSet<(
-        Query<&Transform, With<PlayerEntityTag>>,
-        Query<(&mut Transform, &OrthographicProjection), With<BottomCameraTag>>,
-    )>,
+    target_query: Query<&KineticEntityComponents>,
+    mut camera_query: Query<CameraTrackData, With<Camera>>,
 ) {
-    let mut player_position = Vec2::ZERO;
+    let map_handle: Handle<TiledMapSource> = tiled_asset.tiled_map.clone();
+    let Some(tiled_map) = map_assets.get(&map_handle) else {
+        return;
+    };
 
-    if let Some(player_transform) = param_set.p0().iter().next() {
-        player_position.x = player_transform.translation.x;
-        player_position.y = player_transform.translation.y;
-    }
+    // Map dimensions
+    let map_size = Vec2::new(
+        (tiled_map.rs_tiled_map.width * tiled_map.rs_tiled_map.tile_width) as f32,
+        (tiled_map.rs_tiled_map.height * tiled_map.rs_tiled_map.tile_height) as f32,
+    );
 
-    let map_handle: Handle<TiledMapSource> = tiled_asset.tiled_map.clone();
-    if let Some(tiled_map) = map_assets.get(&map_handle) {
-        // Map dimensions
-        let map_width = (tiled_map.rs_tiled_map.width * tiled_map.rs_tiled_map.tile_width) as f32;
-        let map_height =
-            (tiled_map.rs_tiled_map.height * tiled_map.rs_tiled_map.tile_height) as f32;
-
-        // Map boundaries
-        let map_min_x = 0.0;
-        let map_max_x = map_width;
-        let map_min_y = 0.0;
-        let map_max_y = map_height;
-
-        for (mut camera_transform, orthographic_projection) in param_set.p1().iter_mut() {
-            // Calculate the camera's half-width and half-height using the updated area
-            let camera_width = orthographic_projection.area.width();
-            let camera_height = orthographic_projection.area.height();
-            let half_camera_width = camera_width / 2.0;
-            let half_camera_height = camera_height / 2.0;
-
-            // Adjust for half-tile offset
-            let tile_size = 64.0; // Replace with your TILE_SIZE constant
-
-            // Calculate clamping boundaries
-            let min_x = map_min_x + half_camera_width - tile_size / 2.0;
-            let max_x = map_max_x - half_camera_width - tile_size / 2.0;
-            let min_y = map_min_y + half_camera_height - tile_size / 2.0;
-            let max_y = map_max_y - half_camera_height - tile_size / 2.0;
-
-            // Clamp the camera's position
-            camera_transform.translation.x = player_position.x.clamp(min_x, max_x);
-            camera_transform.translation.y = player_position.y.clamp(min_y, max_y);
+    for (mut camera_transform, orthographic_projection, follow, home) in camera_query.iter_mut() {
+        let target_positions: Vec<Vec2> = follow
+            .targets
+            .iter()
+            .filter_map(|target| target_query.get(*target).ok())
+            .map(|kinetics| {
+                kinetics
+                    .interpolated_position(fixed_time.overstep_fraction())
+                    .truncate()
+            })
+            .collect();
+
+        if target_positions.is_empty() {
+            // nothing to follow, go back home unclamped (the moon sits outside of the map)
+            if let Some(home) = home {
+                camera_transform.translation.x = home.0.x;
+                camera_transform.translation.y = home.0.y;
+            }
+            continue;
         }
+
+        let target_position = target_positions.iter().sum::<Vec2>() / target_positions.len() as f32;
+        let camera_position = clamp_to_map(
+            target_position,
+            orthographic_projection.area.size(),
+            map_size,
+        );
+        camera_transform.translation.x = camera_position.x;
+        camera_transform.translation.y = camera_position.y;
+    }
+}
+
+// the world space rect a camera is looking at right now
+pub fn camera_view(
+    camera_transform: &GlobalTransform,
+    projection: &OrthographicProjection,
+) -> Rect {
+    Rect::from_center_size(
+        camera_transform.translation().truncate() + projection.area.center(),
+        projection.area.size(),
+    )
+}
+
+// roughly what the bottom camera shows while it follows a player standing at `position`, worked
+// out from the simulation alone so fixed tick systems can tell what is on screen without reading
+// the cameras (which only move once a frame). without a map to keep it in it's centred on the player
+pub fn player_view(position: Vec2, map_size: Option<Vec2>) -> Rect {
+    let viewport_size = bottom_screen_viewport().physical_size.as_vec2() * CAMERA_SCALE_MULTIPLIER;
+    let center = map_size.map_or(position, |map_size| {
+        clamp_to_map(position, viewport_size, map_size)
+    });
+    Rect::from_center_size(center, viewport_size)
+}
+
+// keeps a view of view_size inside the map, adjusted for the half-tile offset
+fn clamp_to_map(target_position: Vec2, view_size: Vec2, map_size: Vec2) -> Vec2 {
+    let half_view = view_size / 2.0;
+    let min = half_view - TILE_SIZE / 2.0;
+    let max = map_size - half_view - TILE_SIZE / 2.0;
+    Vec2::new(
+        clamp_or_centre(target_position.x, min.x, max.x),
+        clamp_or_centre(target_position.y, min.y, max.y),
+    )
+}
+
+// along an axis where the map is smaller than the view there is nothing to scroll, so the map
+// stays centred (f32::clamp would panic with min > max)
+fn clamp_or_centre(value: f32, min: f32, max: f32) -> f32 {
+    if min > max {
+        (min + max) / 2.0
+    } else {
+        value.clamp(min, max)
     }
 }
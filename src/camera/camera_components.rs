@@ -1,7 +1,20 @@
-use bevy::prelude::Component;
+use bevy::{
+    math::Vec2,
+    prelude::{Component, Entity},
+};
 
 #[derive(Component, Default)]
 pub struct TopCameraTag;
 
 #[derive(Component, Default)]
 pub struct BottomCameraTag;
+
+// the camera centres on the average position of these, clamped to the map
+#[derive(Component, Default, Debug)]
+pub struct CameraFollow {
+    pub targets: Vec<Entity>,
+}
+
+// where a camera rests when it has nothing to follow, e.g. the top screen framing the moon
+#[derive(Component, Debug)]
+pub struct CameraHome(pub Vec2);
@@ -0,0 +1,148 @@
+use std::f32::consts::{PI, TAU};
+
+use bevy::{
+    math::Vec3,
+    prelude::{Assets, Query, Res, ResMut, Resource, Time, Transform, Visibility, With},
+};
+
+use crate::{
+    environment::moon::{MoonLightSource, MoonPath, MoonTag},
+    materials::{
+        fog::FogMaterial,
+        water::{tilemap_fogs, WaterMaterial},
+    },
+    DAY_LENGTH_SECONDS, MOONRISE_HOUR, NINTENDO_DS_SCREEN_HEIGHT, NINTENDO_DS_SCREEN_WIDTH,
+    START_HOUR,
+};
+
+// the in-game clock, hours run from 0 to 24 and a full day takes day_length_seconds of real time
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct TimeOfDay {
+    pub hours: f32,
+    // days completed since the game started
+    pub day: u32,
+    pub day_length_seconds: f32,
+    pub paused: bool,
+}
+
+impl Default for TimeOfDay {
+    fn default() -> Self {
+        Self {
+            hours: START_HOUR,
+            day: 0,
+            day_length_seconds: DAY_LENGTH_SECONDS,
+            paused: false,
+        }
+    }
+}
+
+impl TimeOfDay {
+    // 0 at midnight, 1 at noon
+    pub fn daylight(&self) -> f32 {
+        0.5 - 0.5 * (self.hours / 24.0 * TAU).cos()
+    }
+
+    // how far the moon is along its arc, 0 at moonrise and 1 at moonset, None while it's down
+    pub fn moon_progress(&self) -> Option<f32> {
+        let since_moonrise = (self.hours - MOONRISE_HOUR).rem_euclid(24.0);
+        (since_moonrise <= 12.0).then_some(since_moonrise / 12.0)
+    }
+
+    // night is cold and blue, dusk and dawn are warm, noon leaves everything as it is
+    pub fn ambient_tint(&self) -> Vec3 {
+        day_curve(
+            self.daylight(),
+            Vec3::new(0.3, 0.35, 0.6),
+            Vec3::new(0.95, 0.7, 0.6),
+            Vec3::ONE,
+        )
+    }
+
+    pub fn fog_color(&self) -> Vec3 {
+        day_curve(
+            self.daylight(),
+            Vec3::new(0.2, 0.25, 0.4),
+            Vec3::new(0.9, 0.7, 0.65),
+            Vec3::ONE,
+        )
+    }
+
+    pub fn fog_density(&self) -> f32 {
+        day_curve(
+            self.daylight(),
+            Vec3::splat(0.8),
+            Vec3::splat(0.55),
+            Vec3::splat(0.35),
+        )
+        .x
+    }
+}
+
+// night at daylight 0, dusk/dawn at 0.5, day at 1
+fn day_curve(daylight: f32, night: Vec3, dusk: Vec3, day: Vec3) -> Vec3 {
+    if daylight < 0.5 {
+        night.lerp(dusk, daylight * 2.0)
+    } else {
+        dusk.lerp(day, (daylight - 0.5) * 2.0)
+    }
+}
+
+pub fn advance_time_of_day(time: Res<Time>, mut time_of_day: ResMut<TimeOfDay>) {
+    if time_of_day.paused || time_of_day.day_length_seconds <= 0.0 {
+        return;
+    }
+    time_of_day.hours += time.delta_seconds() / time_of_day.day_length_seconds * 24.0;
+    while time_of_day.hours >= 24.0 {
+        time_of_day.hours -= 24.0;
+        time_of_day.day += 1;
+    }
+}
+
+// the moon rises on the left of the top screen, peaks over its anchor and sets on the right,
+// brightest and whitest up high and dim and orange near the horizon
+pub fn move_moon_with_time_of_day(
+    time_of_day: Res<TimeOfDay>,
+    mut query: Query<
+        (
+            &MoonPath,
+            &mut Transform,
+            &mut MoonLightSource,
+            &mut Visibility,
+        ),
+        With<MoonTag>,
+    >,
+) {
+    for (moon_path, mut transform, mut moonlight, mut visibility) in query.iter_mut() {
+        let Some(progress) = time_of_day.moon_progress() else {
+            moonlight.intensity = 0.0;
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        *visibility = Visibility::Inherited;
+
+        let altitude = (progress * PI).sin();
+        transform.translation.x =
+            moon_path.anchor.x + (progress * 2.0 - 1.0) * NINTENDO_DS_SCREEN_WIDTH;
+        transform.translation.y =
+            moon_path.anchor.y - (1.0 - altitude) * NINTENDO_DS_SCREEN_HEIGHT / 2.0;
+
+        moonlight.position = transform.translation.truncate();
+        moonlight.intensity = altitude;
+        moonlight.color = Vec3::new(1.0, 0.75, 0.55).lerp(Vec3::new(0.9, 0.95, 1.0), altitude);
+    }
+}
+
+pub fn apply_time_of_day_to_fog(
+    time_of_day: Res<TimeOfDay>,
+    mut fog_materials: ResMut<Assets<FogMaterial>>,
+    mut water_materials: ResMut<Assets<WaterMaterial>>,
+) {
+    let tint = time_of_day.ambient_tint();
+    let fog_color = time_of_day.fog_color();
+    let density = time_of_day.fog_density();
+    for fog in tilemap_fogs(&mut fog_materials, &mut water_materials) {
+        fog.tint = tint;
+        fog.fog_color = fog_color;
+        fog.density = density;
+    }
+}
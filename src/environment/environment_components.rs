@@ -0,0 +1,37 @@
+use bevy::{
+    math::Rect,
+    prelude::{Component, Entity},
+    time::Timer,
+};
+
+use crate::{
+    ai::ai_components::{FlockMember, SteeringBehavior},
+    environment::moon_phase::MoonPhaseName,
+    lighting::lighting_components::Light2d,
+};
+
+// what a spawner stamps out, everything else comes from the environment entity assets
+#[derive(Clone, Copy, Debug)]
+pub struct EnvironmentTemplate {
+    pub behavior: SteeringBehavior,
+    pub flock: Option<FlockMember>,
+    pub light: Option<Light2d>,
+}
+
+// keeps up to max_population entities of its template alive somewhere inside area,
+// adding one every time interval finishes, and only while the moon is in moon_phase if it has one
+#[derive(Component, Debug)]
+pub struct EnvironmentSpawner {
+    pub template: EnvironmentTemplate,
+    pub area: Rect,
+    pub interval: Timer,
+    pub max_population: usize,
+    pub moon_phase: Option<MoonPhaseName>,
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+pub struct SpawnedBy(pub Entity);
+
+// seconds the entity has been outside of both cameras' view in a row
+#[derive(Component, Default, Debug)]
+pub struct OffscreenTime(pub f32);
@@ -0,0 +1,9 @@
+use bevy::prelude::{Entity, Handle, Resource, TextureAtlasLayout};
+
+// environment entities that went off screen for too long get hidden, stripped of their
+// kinetics and parked here for the next spawner to pick back up instead of despawned
+#[derive(Resource)]
+pub struct EnvironmentEntityPool {
+    pub layout: Handle<TextureAtlasLayout>,
+    pub free: Vec<Entity>,
+}
@@ -1,29 +1,72 @@
+use std::f32::consts::TAU;
+
 use bevy::{
     core::Name,
-    math::UVec2,
+    log::info,
+    math::{Rect, UVec2, Vec2},
     prelude::{
-        Commands, Res, ResMut, TextureAtlas, TextureAtlasLayout, Timer, TimerMode, Transform,
+        Commands, Entity, Handle, Query, Res, ResMut, TextureAtlas, TextureAtlasLayout, Time,
+        Timer, TimerMode, Transform, Visibility, With,
     },
     sprite::SpriteBundle,
 };
 use bevy_asset::Assets;
+use rand::Rng;
+use tiled::{LayerType, Map, ObjectData, ObjectShape, Properties, PropertyValue};
 
 use crate::{
+    ai::ai_components::{
+        FlockMember, FollowPath, PathNotFound, PathRequest, SteeringAgent, SteeringBehavior,
+    },
     anime::{
-        anime_components::{AnimationComponent, AnimationTimer},
+        anime_components::{
+            AnimationComponent, AnimationFrameMarkers, AnimationTimer, FrameMarker, WakeEmitter,
+        },
         anime_res::EnvironmentEntityAnimationAssets,
     },
     bundles::EnvironmentEntityBundle,
-    kinetic_components::{EnvironmentEntityTag, KineticEntityComponents},
+    camera::camera_2d_sys::player_view,
+    environment::{
+        environment_components::{
+            EnvironmentSpawner, EnvironmentTemplate, OffscreenTime, SpawnedBy,
+        },
+        environment_res::EnvironmentEntityPool,
+        moon_phase::{LunarPhase, MoonPhaseName},
+    },
+    kinetic_components::{
+        EnvironmentEntityTag, KineticEntityComponents, PlayerEntityTag, SteeringInput, TileCollider,
+    },
+    lighting::lighting_components::Light2d,
+    map::{
+        tiled_components::TileOccupancy,
+        tiled_res::{TileCollisionMap, TiledMapAssets, TiledMapSource},
+        tiled_sys::{color_property, float_property, tiled_object_world_position},
+    },
+    rng_res::GameRng,
     ENVIRONMENT_ENTITY_ANIMATION_SPEED, ENVIRONMENT_ENTITY_ANIMATION_TEXTURE_COLUMN_LENGTH,
     ENVIRONMENT_ENTITY_ANIMATION_TEXTURE_END_IDX, ENVIRONMENT_ENTITY_ANIMATION_TEXTURE_ROW_LENGTH,
-    ENVIRONMENT_ENTITY_ANIMATION_TEXTURE_START_IDX, ENVIRONMENT_ENTITY_SPAWN_X,
-    ENVIRONMENT_ENTITY_SPAWN_Y, ENVIRONMENT_ENTITY_Z_LEVEL, TILE_SIZE,
+    ENVIRONMENT_ENTITY_ANIMATION_TEXTURE_START_IDX, ENVIRONMENT_ENTITY_OBJECT_TYPE,
+    ENVIRONMENT_ENTITY_ORBIT_RADIUS, ENVIRONMENT_ENTITY_SPAWN_X, ENVIRONMENT_ENTITY_SPAWN_Y,
+    ENVIRONMENT_ENTITY_SPLASH_FRAME, ENVIRONMENT_ENTITY_Z_LEVEL,
+    ENVIRONMENT_OFFSCREEN_DESPAWN_SECONDS, ENVIRONMENT_SPAWNER_DEFAULT_MAX_POPULATION,
+    ENVIRONMENT_SPAWNER_DEFAULT_RATE, ENVIRONMENT_SPAWNER_OBJECT_TYPE, ENVIRONMENT_SPAWN_ATTEMPTS,
+    GLOW_DEFAULT_RADIUS, LIGHT_DEFAULT_INTENSITY, SCHOOL_DEFAULT_COUNT, SCHOOL_DEFAULT_RADIUS,
+    SCHOOL_SPAWNER_OBJECT_TYPE, TILE_SIZE,
 };
 
+// what the map puts in the water, entities that are there from the start and spawners that keep
+// adding more over time
+pub struct MapEnvironment {
+    pub entities: Vec<(Vec2, EnvironmentTemplate)>,
+    pub spawners: Vec<EnvironmentSpawner>,
+}
+
 pub fn spawn_environment_entity(
     mut commands: Commands,
     environment_entity_assets: Res<EnvironmentEntityAnimationAssets>,
+    mut game_rng: ResMut<GameRng>,
+    tiled_asset: Res<TiledMapAssets>,
+    map_assets: Res<Assets<TiledMapSource>>,
     mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
 ) {
     let environment_texture_atlas_layout =
@@ -35,11 +78,190 @@ pub fn spawn_environment_entity(
             None,
         ));
 
-    let transform = Transform::from_xyz(
-        ENVIRONMENT_ENTITY_SPAWN_X * TILE_SIZE,
-        ENVIRONMENT_ENTITY_SPAWN_Y * TILE_SIZE,
-        ENVIRONMENT_ENTITY_Z_LEVEL,
+    let map_environment = match map_assets.get(&tiled_asset.tiled_map) {
+        Some(tiled_map) => map_environment(&tiled_map.rs_tiled_map, &mut game_rng),
+        None => default_map_environment(),
+    };
+    spawn_map_environment(
+        &mut commands,
+        &environment_entity_assets,
+        environment_texture_atlas_layout,
+        map_environment,
     );
+}
+
+// one entity per `environment_entity` object in the map, a whole flocking school per `school_spawner`
+// object, a spawner per `environment_spawner` object, or a lone wanderer at the default spawn when
+// the map doesn't have any of those
+pub fn map_environment(map: &Map, game_rng: &mut GameRng) -> MapEnvironment {
+    let mut entities = Vec::new();
+    let mut spawners = Vec::new();
+    for layer in map.layers() {
+        let LayerType::Objects(object_layer) = layer.layer_type() else {
+            continue;
+        };
+        for object in object_layer.objects() {
+            let position = tiled_object_world_position(&object);
+            let behavior = steering_behavior_from_properties(&object.properties, position);
+            let light = glow_from_properties(&object.properties);
+            if object.user_type == ENVIRONMENT_ENTITY_OBJECT_TYPE {
+                entities.push((
+                    position,
+                    EnvironmentTemplate {
+                        behavior,
+                        flock: None,
+                        light,
+                    },
+                ));
+            } else if object.user_type == SCHOOL_SPAWNER_OBJECT_TYPE {
+                let count = float_property(&object.properties, "count")
+                    .map_or(SCHOOL_DEFAULT_COUNT, |count| count.max(0.0) as usize);
+                let radius = float_property(&object.properties, "radius")
+                    .unwrap_or(SCHOOL_DEFAULT_RADIUS)
+                    * TILE_SIZE;
+                let flock = FlockMember { flock: object.id() };
+                for _ in 0..count {
+                    // scattered uniformly over the spawner's circle
+                    let angle = game_rng.rng.gen_range(0.0..TAU);
+                    let distance = radius * game_rng.rng.gen::<f32>().sqrt();
+                    let offset = Vec2::from_angle(angle) * distance;
+                    entities.push((
+                        position + offset,
+                        EnvironmentTemplate {
+                            behavior,
+                            flock: Some(flock),
+                            light,
+                        },
+                    ));
+                }
+            } else if object.user_type == ENVIRONMENT_SPAWNER_OBJECT_TYPE {
+                spawners.push(environment_spawner_from_object(
+                    &object,
+                    position,
+                    EnvironmentTemplate {
+                        behavior,
+                        flock: None,
+                        light,
+                    },
+                ));
+            }
+        }
+    }
+    if entities.is_empty() && spawners.is_empty() {
+        return default_map_environment();
+    }
+    MapEnvironment { entities, spawners }
+}
+
+fn default_map_environment() -> MapEnvironment {
+    MapEnvironment {
+        entities: vec![(
+            Vec2::new(
+                ENVIRONMENT_ENTITY_SPAWN_X * TILE_SIZE,
+                ENVIRONMENT_ENTITY_SPAWN_Y * TILE_SIZE,
+            ),
+            EnvironmentTemplate {
+                behavior: SteeringBehavior::Wander,
+                flock: None,
+                light: None,
+            },
+        )],
+        spawners: Vec::new(),
+    }
+}
+
+// split from spawn_environment_entity so headless runs (the replay test), which can't load a
+// TiledMapSource, end up with the same entities
+pub fn spawn_map_environment(
+    commands: &mut Commands,
+    environment_entity_assets: &EnvironmentEntityAnimationAssets,
+    texture_atlas_layout: Handle<TextureAtlasLayout>,
+    map_environment: MapEnvironment,
+) {
+    for (position, template) in map_environment.entities {
+        let entity = spawn_environment_entity_sprite(
+            commands,
+            environment_entity_assets,
+            texture_atlas_layout.clone(),
+            position,
+        );
+        activate_environment_entity(commands, entity, position, template);
+    }
+    for spawner in map_environment.spawners {
+        commands
+            .spawn(spawner)
+            .insert(Name::new("EnvironmentSpawner"));
+    }
+
+    commands.insert_resource(EnvironmentEntityPool {
+        layout: texture_atlas_layout,
+        free: Vec::new(),
+    });
+}
+
+// a rectangle object spawns inside of itself, a point object inside of its `radius` in tiles
+fn environment_spawner_from_object(
+    object: &ObjectData,
+    position: Vec2,
+    template: EnvironmentTemplate,
+) -> EnvironmentSpawner {
+    let area = match object.shape {
+        ObjectShape::Rect { width, height } => {
+            Rect::from_corners(position, position + Vec2::new(width, height))
+        }
+        _ => Rect::from_center_half_size(
+            position,
+            Vec2::splat(
+                float_property(&object.properties, "radius").unwrap_or(SCHOOL_DEFAULT_RADIUS)
+                    * TILE_SIZE,
+            ),
+        ),
+    };
+    let rate = float_property(&object.properties, "rate")
+        .unwrap_or(ENVIRONMENT_SPAWNER_DEFAULT_RATE)
+        .max(f32::EPSILON);
+    let max_population = float_property(&object.properties, "max_population")
+        .map_or(ENVIRONMENT_SPAWNER_DEFAULT_MAX_POPULATION, |max| {
+            max.max(0.0) as usize
+        });
+    let flock = matches!(
+        object.properties.get("flock"),
+        Some(PropertyValue::BoolValue(true))
+    )
+    .then_some(FlockMember { flock: object.id() });
+    let template = EnvironmentTemplate { flock, ..template };
+    let moon_phase = match object.properties.get("moon_phase") {
+        Some(PropertyValue::StringValue(name)) => {
+            let moon_phase = MoonPhaseName::from_name(name);
+            if moon_phase.is_none() {
+                info!(
+                    "Ignoring unknown moon_phase {:?} on object {}",
+                    name,
+                    object.id()
+                );
+            }
+            moon_phase
+        }
+        _ => None,
+    };
+
+    EnvironmentSpawner {
+        template,
+        area,
+        interval: Timer::from_seconds(1.0 / rate, TimerMode::Repeating),
+        max_population,
+        moon_phase,
+    }
+}
+
+// everything an environment entity keeps while it sits in the pool
+fn spawn_environment_entity_sprite(
+    commands: &mut Commands,
+    environment_entity_assets: &EnvironmentEntityAnimationAssets,
+    texture_atlas_layout: Handle<TextureAtlasLayout>,
+    position: Vec2,
+) -> Entity {
+    let transform = Transform::from_xyz(position.x, position.y, ENVIRONMENT_ENTITY_Z_LEVEL);
 
     let sprite_sheet_bundle = SpriteBundle {
         texture: environment_entity_assets.animation_image_handle.clone(),
@@ -48,7 +270,7 @@ pub fn spawn_environment_entity(
     };
 
     let texture_atlas = TextureAtlas {
-        layout: environment_texture_atlas_layout,
+        layout: texture_atlas_layout,
         index: ENVIRONMENT_ENTITY_ANIMATION_TEXTURE_START_IDX,
     };
 
@@ -63,6 +285,7 @@ pub fn spawn_environment_entity(
         x_axis_displacement: 0.0,
         position: transform.translation,
         prev_position: transform.translation,
+        heading: 0.0,
     };
     commands
         .spawn(EnvironmentEntityBundle {
@@ -76,5 +299,231 @@ pub fn spawn_environment_entity(
                 TimerMode::Repeating,
             )),
         })
-        .insert(EnvironmentEntityTag);
+        .insert(AnimationFrameMarkers(vec![FrameMarker {
+            frame: ENVIRONMENT_ENTITY_SPLASH_FRAME,
+            name: "splash".to_string(),
+        }]))
+        .id()
+}
+
+// everything that makes an environment entity swim around, stripped again by recycle_environment_entity
+fn activate_environment_entity(
+    commands: &mut Commands,
+    entity: Entity,
+    position: Vec2,
+    template: EnvironmentTemplate,
+) {
+    let position = position.extend(ENVIRONMENT_ENTITY_Z_LEVEL);
+    let mut environment_entity = commands.entity(entity);
+    environment_entity
+        .insert(KineticEntityComponents {
+            y_axis_displacement: 0.0,
+            x_axis_displacement: 0.0,
+            position,
+            prev_position: position,
+            heading: 0.0,
+        })
+        .insert(Transform::from_translation(position))
+        .insert(Visibility::Inherited)
+        .insert(EnvironmentEntityTag)
+        .insert(SteeringAgent::new(template.behavior))
+        .insert(SteeringInput::default())
+        .insert(TileCollider)
+        .insert(WakeEmitter::default());
+    if let Some(flock) = template.flock {
+        environment_entity.insert(flock);
+    }
+    if let Some(light) = template.light {
+        environment_entity.insert(light);
+    }
+}
+
+fn recycle_environment_entity(
+    commands: &mut Commands,
+    pool: &mut EnvironmentEntityPool,
+    entity: Entity,
+) {
+    commands
+        .entity(entity)
+        .remove::<(
+            EnvironmentEntityTag,
+            KineticEntityComponents,
+            SteeringAgent,
+            SteeringInput,
+            TileCollider,
+            FlockMember,
+            Light2d,
+            WakeEmitter,
+            TileOccupancy,
+            PathRequest,
+            FollowPath,
+            PathNotFound,
+            SpawnedBy,
+            OffscreenTime,
+        )>()
+        .insert(Visibility::Hidden);
+    pool.free.push(entity);
+}
+
+// prefers a spot no player can see so nothing pops into view, small maps that are always fully on
+// screen fall back to anywhere walkable in the area. has to run in FixedUpdate since the spawn
+// positions come out of GameRng, and every attempt gets rolled up front so how many values get
+// drawn doesn't depend on which of them turn out to be usable
+#[allow(clippy::too_many_arguments)]
+pub fn run_environment_spawners(
+    mut commands: Commands,
+    time: Res<Time>,
+    environment_entity_assets: Res<EnvironmentEntityAnimationAssets>,
+    collision_map: Option<Res<TileCollisionMap>>,
+    mut game_rng: ResMut<GameRng>,
+    mut pool: ResMut<EnvironmentEntityPool>,
+    lunar_phase: Option<Res<LunarPhase>>,
+    player_query: Query<&KineticEntityComponents, With<PlayerEntityTag>>,
+    spawned_query: Query<&SpawnedBy>,
+    mut spawner_query: Query<(Entity, &mut EnvironmentSpawner)>,
+) {
+    let views = player_views(collision_map.as_deref(), &player_query);
+
+    for (spawner_entity, mut spawner) in spawner_query.iter_mut() {
+        spawner.interval.tick(time.delta());
+        if !spawner.interval.just_finished() {
+            continue;
+        }
+        // e.g. creatures that only come out on a full moon
+        if let (Some(moon_phase), Some(lunar_phase)) = (spawner.moon_phase, lunar_phase.as_ref()) {
+            if lunar_phase.name() != moon_phase {
+                continue;
+            }
+        }
+        let population = spawned_query
+            .iter()
+            .filter(|spawned_by| spawned_by.0 == spawner_entity)
+            .count();
+        if population >= spawner.max_population {
+            continue;
+        }
+
+        let attempts: Vec<Vec2> = (0..ENVIRONMENT_SPAWN_ATTEMPTS)
+            .map(|_| {
+                Vec2::new(
+                    game_rng
+                        .rng
+                        .gen_range(spawner.area.min.x..=spawner.area.max.x),
+                    game_rng
+                        .rng
+                        .gen_range(spawner.area.min.y..=spawner.area.max.y),
+                )
+            })
+            .collect();
+        let walkable = |position: &&Vec2| {
+            collision_map
+                .as_ref()
+                .is_none_or(|collision_map| collision_map.is_walkable_at(&position.extend(0.0)))
+        };
+        let unseen = |position: &&Vec2| !views.iter().any(|view| view.contains(**position));
+        let Some(&position) = attempts
+            .iter()
+            .filter(walkable)
+            .find(unseen)
+            .or_else(|| attempts.iter().find(walkable))
+        else {
+            continue;
+        };
+
+        let entity = pool.free.pop().unwrap_or_else(|| {
+            spawn_environment_entity_sprite(
+                &mut commands,
+                &environment_entity_assets,
+                pool.layout.clone(),
+                position,
+            )
+        });
+        activate_environment_entity(&mut commands, entity, position, spawner.template);
+        commands
+            .entity(entity)
+            .insert(SpawnedBy(spawner_entity))
+            .insert(OffscreenTime::default());
+    }
+}
+
+// only spawned entities get recycled, the ones placed in the map stick around
+pub fn despawn_offscreen_environment_entities(
+    mut commands: Commands,
+    time: Res<Time>,
+    collision_map: Option<Res<TileCollisionMap>>,
+    mut pool: ResMut<EnvironmentEntityPool>,
+    player_query: Query<&KineticEntityComponents, With<PlayerEntityTag>>,
+    mut query: Query<(Entity, &KineticEntityComponents, &mut OffscreenTime), With<SpawnedBy>>,
+) {
+    let views = player_views(collision_map.as_deref(), &player_query);
+
+    for (entity, kinetics, mut offscreen_time) in query.iter_mut() {
+        let position = kinetics.position.truncate();
+        if views.iter().any(|view| view.contains(position)) {
+            offscreen_time.0 = 0.0;
+            continue;
+        }
+        offscreen_time.0 += time.delta_seconds();
+        if offscreen_time.0 >= ENVIRONMENT_OFFSCREEN_DESPAWN_SECONDS {
+            recycle_environment_entity(&mut commands, &mut pool, entity);
+        }
+    }
+}
+
+// what the players can see, grown by a tile so sprites half in view still count. worked out from
+// where they are in the simulation instead of from the cameras, which move with the frame rate
+fn player_views(
+    collision_map: Option<&TileCollisionMap>,
+    player_query: &Query<&KineticEntityComponents, With<PlayerEntityTag>>,
+) -> Vec<Rect> {
+    let map_size = collision_map.map(|collision_map| {
+        Vec2::new(collision_map.size.x as f32, collision_map.size.y as f32) * TILE_SIZE
+    });
+    player_query
+        .iter()
+        .map(|kinetics| player_view(kinetics.position.truncate(), map_size).inflate(TILE_SIZE))
+        .collect()
+}
+
+// bioluminescent critters, a `glow` color with an optional `glow_intensity` and `glow_radius` in tiles
+fn glow_from_properties(properties: &Properties) -> Option<Light2d> {
+    let color = color_property(properties, "glow")?;
+    Some(Light2d {
+        color,
+        intensity: float_property(properties, "glow_intensity").unwrap_or(LIGHT_DEFAULT_INTENSITY),
+        radius: float_property(properties, "glow_radius").unwrap_or(GLOW_DEFAULT_RADIUS)
+            * TILE_SIZE,
+    })
+}
+
+// `behavior` is one of wander, seek, flee_player, follow_player or orbit. seek and orbit read their
+// point from `target_x`/`target_y` in tiles (defaulting to the spawn point), orbit its `radius` in tiles
+fn steering_behavior_from_properties(properties: &Properties, position: Vec2) -> SteeringBehavior {
+    let target = match (
+        float_property(properties, "target_x"),
+        float_property(properties, "target_y"),
+    ) {
+        (Some(x), Some(y)) => Vec2::new(x, y) * TILE_SIZE,
+        _ => position,
+    };
+
+    match properties.get("behavior") {
+        Some(PropertyValue::StringValue(behavior)) => match behavior.as_str() {
+            "wander" => SteeringBehavior::Wander,
+            "seek" => SteeringBehavior::Seek { target },
+            "flee_player" => SteeringBehavior::FleePlayer,
+            "follow_player" => SteeringBehavior::FollowPlayer,
+            "orbit" => SteeringBehavior::Orbit {
+                center: target,
+                radius: float_property(properties, "radius")
+                    .unwrap_or(ENVIRONMENT_ENTITY_ORBIT_RADIUS)
+                    * TILE_SIZE,
+            },
+            other => {
+                info!("Unknown behavior {:?}, falling back to wander", other);
+                SteeringBehavior::Wander
+            }
+        },
+        _ => SteeringBehavior::Wander,
+    }
 }
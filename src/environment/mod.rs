@@ -1,2 +1,7 @@
+pub mod day_night;
+pub mod environment_components;
+pub mod environment_res;
 pub mod environment_sys;
 pub mod moon;
+pub mod moon_phase;
+pub mod weather;
@@ -1,22 +1,25 @@
 use bevy::{
-    math::{Vec2, Vec3},
-    prelude::{Commands, Component, Res, Resource, Transform},
-    sprite::{Sprite, SpriteBundle},
+    math::{primitives::Rectangle, Vec2, Vec3},
+    prelude::{Commands, Component, Mesh, Res, ResMut, Resource, Transform},
+    sprite::MaterialMesh2dBundle,
     utils::default,
 };
-use bevy_asset::Handle;
+use bevy_asset::{Assets, Handle};
 use bevy_asset_loader::asset_collection::AssetCollection;
 use bevy_render::texture::Image;
 
-use crate::{NINTENDO_DS_SCREEN_HEIGHT, NINTENDO_DS_SCREEN_WIDTH};
+use crate::{
+    materials::moon_phase::MoonPhaseMaterial, MOON_DISC_CENTER, MOON_DISC_RADIUS, MOON_EARTHSHINE,
+    MOON_SIZE, NINTENDO_DS_SCREEN_HEIGHT,
+};
 
 #[derive(Component, Default)]
 pub struct MoonTag;
 
 #[derive(AssetCollection, Resource)]
 pub struct MoonAsset {
-    #[asset(path = "sprite_data/ollie_bacon_daytime_moon.png")]
-    pub background: Handle<Image>,
+    #[asset(path = "sprite_data/full_moon.png")]
+    pub texture: Handle<Image>,
 }
 
 #[derive(Component, Debug, Clone)]
@@ -26,26 +29,35 @@ pub struct MoonLightSource {
     pub color: Vec3,
 }
 
-pub fn place_moon(mut commands: Commands, moon_asset: Res<MoonAsset>) {
-    let moon_image = moon_asset.background.clone();
+// where the moon sits at the top of its arc across the top screen, see day_night
+#[derive(Component, Debug, Clone, Copy)]
+pub struct MoonPath {
+    pub anchor: Vec2,
+}
+
+pub fn place_moon(
+    mut commands: Commands,
+    moon_asset: Res<MoonAsset>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<MoonPhaseMaterial>>,
+) {
+    let moon_image = moon_asset.texture.clone();
     // TODO: make a MoonLightBundle
     let moon_transform = Transform::from_xyz(0.0, 640.0 + NINTENDO_DS_SCREEN_HEIGHT, 1.0);
     commands
         .spawn((
-            SpriteBundle {
-                sprite: Sprite {
-                    custom_size: Option::from(Vec2::new(
-                        NINTENDO_DS_SCREEN_HEIGHT,
-                        NINTENDO_DS_SCREEN_WIDTH,
-                    )),
-                    ..default()
-                },
+            // a mesh instead of a sprite so the phase can be masked on in the shader
+            MaterialMesh2dBundle {
+                mesh: meshes.add(Rectangle::new(MOON_SIZE, MOON_SIZE)).into(),
+                material: materials.add(MoonPhaseMaterial {
+                    phase: 0.5,
+                    earthshine: MOON_EARTHSHINE,
+                    disc_center: MOON_DISC_CENTER,
+                    disc_radius: MOON_DISC_RADIUS,
+                    texture: moon_image,
+                }),
                 transform: moon_transform,
-                global_transform: Default::default(),
-                texture: moon_image,
-                visibility: Default::default(),
-                inherited_visibility: Default::default(),
-                view_visibility: Default::default(),
+                ..default()
             },
             MoonLightSource {
                 position: Vec2::new(moon_transform.translation.x, moon_transform.translation.y),
@@ -53,5 +65,8 @@ pub fn place_moon(mut commands: Commands, moon_asset: Res<MoonAsset>) {
                 color: Vec3::new(1.0, 1.0, 1.0),
             },
         ))
+        .insert(MoonPath {
+            anchor: moon_transform.translation.truncate(),
+        })
         .insert(MoonTag);
 }
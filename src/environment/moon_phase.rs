@@ -0,0 +1,175 @@
+use std::{
+    env,
+    f32::consts::TAU,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::{
+    log::{info, warn},
+    prelude::{Event, EventWriter, Handle, Query, Res, ResMut, Resource, With},
+};
+use bevy_asset::Assets;
+
+use crate::{
+    environment::{
+        day_night::TimeOfDay,
+        moon::{MoonLightSource, MoonTag},
+    },
+    materials::moon_phase::MoonPhaseMaterial,
+    LUNAR_CYCLE_GAME_DAYS, MOONLIGHT_NEW_MOON_INTENSITY, REAL_LUNAR_CYCLE_DAYS,
+    REAL_NEW_MOON_UNIX_SECONDS, START_HOUR,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LunarPhaseSource {
+    // one cycle every LUNAR_CYCLE_GAME_DAYS of the in-game clock
+    GameCalendar,
+    // whatever the moon is doing outside right now
+    RealDate,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MoonPhaseName {
+    New,
+    WaxingCrescent,
+    FirstQuarter,
+    WaxingGibbous,
+    Full,
+    WaningGibbous,
+    LastQuarter,
+    WaningCrescent,
+}
+
+impl MoonPhaseName {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "new" => Some(Self::New),
+            "waxing_crescent" => Some(Self::WaxingCrescent),
+            "first_quarter" => Some(Self::FirstQuarter),
+            "waxing_gibbous" => Some(Self::WaxingGibbous),
+            "full" => Some(Self::Full),
+            "waning_gibbous" => Some(Self::WaningGibbous),
+            "last_quarter" => Some(Self::LastQuarter),
+            "waning_crescent" => Some(Self::WaningCrescent),
+            _ => None,
+        }
+    }
+}
+
+// the moon's place in its cycle, for the renderer and for anything in gameplay that cares
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct LunarPhase {
+    pub source: LunarPhaseSource,
+    // 0 is a new moon, 0.5 full and back to new at 1
+    pub phase: f32,
+}
+
+impl Default for LunarPhase {
+    fn default() -> Self {
+        Self {
+            source: LunarPhaseSource::GameCalendar,
+            phase: 0.5,
+        }
+    }
+}
+
+impl LunarPhase {
+    // how much of the disc is lit, 0 at new moon and 1 at full
+    pub fn illumination(&self) -> f32 {
+        0.5 - 0.5 * (self.phase * TAU).cos()
+    }
+
+    // each named phase covers an eighth of the cycle, centred on its exact point
+    pub fn name(&self) -> MoonPhaseName {
+        match ((self.phase * 8.0).round() as u32) % 8 {
+            0 => MoonPhaseName::New,
+            1 => MoonPhaseName::WaxingCrescent,
+            2 => MoonPhaseName::FirstQuarter,
+            3 => MoonPhaseName::WaxingGibbous,
+            4 => MoonPhaseName::Full,
+            5 => MoonPhaseName::WaningGibbous,
+            6 => MoonPhaseName::LastQuarter,
+            _ => MoonPhaseName::WaningCrescent,
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.name() == MoonPhaseName::Full
+    }
+
+    pub fn is_new(&self) -> bool {
+        self.name() == MoonPhaseName::New
+    }
+}
+
+// sent whenever the moon moves on to the next named phase
+#[derive(Event, Debug, Clone, Copy)]
+pub struct MoonPhaseChanged {
+    pub previous: MoonPhaseName,
+    pub current: MoonPhaseName,
+}
+
+// the real date differs between recording a replay and playing it back, and spawners go by the
+// moon, so `--real-moon` only applies outside of replays
+pub fn setup_lunar_phase_from_args(mut lunar_phase: ResMut<LunarPhase>) {
+    let args: Vec<String> = env::args().collect();
+    if !args.iter().any(|arg| arg == "--real-moon") {
+        return;
+    }
+    if args
+        .iter()
+        .any(|arg| arg == "--record" || arg == "--replay")
+    {
+        warn!("Ignoring --real-moon while recording or playing back a replay");
+        return;
+    }
+    info!("Moon phase follows the real date");
+    lunar_phase.source = LunarPhaseSource::RealDate;
+}
+
+// has to run after advance_time_of_day, in FixedUpdate since spawners check the phase
+pub fn update_lunar_phase(
+    time_of_day: Res<TimeOfDay>,
+    mut lunar_phase: ResMut<LunarPhase>,
+    mut phase_changed_writer: EventWriter<MoonPhaseChanged>,
+) {
+    let previous = lunar_phase.name();
+    lunar_phase.phase = match lunar_phase.source {
+        LunarPhaseSource::GameCalendar => {
+            // counted from the hour the game starts at, which is on a full moon
+            let days = time_of_day.day as f32 + (time_of_day.hours - START_HOUR) / 24.0;
+            (0.5 + days / LUNAR_CYCLE_GAME_DAYS).rem_euclid(1.0)
+        }
+        LunarPhaseSource::RealDate => real_lunar_phase(),
+    };
+
+    let current = lunar_phase.name();
+    if current != previous {
+        phase_changed_writer.send(MoonPhaseChanged { previous, current });
+    }
+}
+
+// days since a known new moon folded into the synodic month, close enough for a night sky
+fn real_lunar_phase() -> f32 {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0.0, |since_epoch| since_epoch.as_secs_f64());
+    let days = (now - REAL_NEW_MOON_UNIX_SECONDS) / 86_400.0;
+    (days / REAL_LUNAR_CYCLE_DAYS).rem_euclid(1.0) as f32
+}
+
+// has to run after move_moon_with_time_of_day, a thin crescent gives off less light than a full moon
+pub fn apply_lunar_phase_to_moon(
+    lunar_phase: Res<LunarPhase>,
+    mut materials: ResMut<Assets<MoonPhaseMaterial>>,
+    mut query: Query<(&Handle<MoonPhaseMaterial>, &mut MoonLightSource), With<MoonTag>>,
+) {
+    let phase_intensity = MOONLIGHT_NEW_MOON_INTENSITY
+        + (1.0 - MOONLIGHT_NEW_MOON_INTENSITY) * lunar_phase.illumination();
+    for (material_handle, mut moonlight) in query.iter_mut() {
+        moonlight.intensity *= phase_intensity;
+        if let Some(material) = materials.get_mut(material_handle) {
+            material.phase = lunar_phase.phase;
+        }
+    }
+}
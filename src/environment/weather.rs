@@ -0,0 +1,340 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::{
+    color::Color,
+    log::info,
+    math::{Quat, Rect, Vec2, Vec3},
+    prelude::{
+        Camera, Commands, Component, Entity, GlobalTransform, Local, OrthographicProjection, Query,
+        Res, ResMut, Resource, Time, Timer, TimerMode, Transform, With,
+    },
+    sprite::{Sprite, SpriteBundle},
+    utils::default,
+};
+use bevy_asset::Assets;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use tiled::{Map, Properties, PropertyValue};
+
+use crate::{
+    camera::camera_2d_sys::camera_view,
+    map::{
+        tiled_res::{TiledMapAssets, TiledMapSource},
+        tiled_sys::float_property,
+    },
+    materials::{
+        fog::FogMaterial,
+        water::{tilemap_fogs, WaterMaterial},
+    },
+    rng_res::{EffectsRng, GameRng},
+    RAIN_DROP_FALL_SPEED, RAIN_DROP_LENGTH, RAIN_DROP_WIND_PUSH, RAIN_Z_LEVEL, TILE_SIZE,
+    WEATHER_CHANGE_SECONDS, WEATHER_FOG_DRIFT_SPEED, WEATHER_TRANSITION_SECONDS,
+    WEATHER_WATER_ANIMATION_WIND_SCALE,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WeatherKind {
+    Calm,
+    Breezy,
+    Rain,
+    Storm,
+}
+
+impl WeatherKind {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim() {
+            "calm" => Some(Self::Calm),
+            "breezy" => Some(Self::Breezy),
+            "rain" => Some(Self::Rain),
+            "storm" => Some(Self::Storm),
+            _ => None,
+        }
+    }
+
+    pub fn conditions(&self) -> WeatherConditions {
+        match self {
+            Self::Calm => WeatherConditions {
+                wind_strength: 0.2,
+                fog_density: 1.0,
+                rain_rate: 0.0,
+            },
+            Self::Breezy => WeatherConditions {
+                wind_strength: 0.6,
+                fog_density: 0.7,
+                rain_rate: 0.0,
+            },
+            Self::Rain => WeatherConditions {
+                wind_strength: 0.5,
+                fog_density: 1.2,
+                rain_rate: 60.0,
+            },
+            Self::Storm => WeatherConditions {
+                wind_strength: 1.0,
+                fog_density: 1.5,
+                rain_rate: 180.0,
+            },
+        }
+    }
+}
+
+// what a weather kind does to the world, blended between kinds while the weather is changing
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WeatherConditions {
+    // 0 is still air, 1 is a full storm
+    pub wind_strength: f32,
+    // multiplied onto whatever fog density the time of day asks for
+    pub fog_density: f32,
+    // rain drops per second per screen
+    pub rain_rate: f32,
+}
+
+impl WeatherConditions {
+    pub fn lerp(&self, other: &Self, t: f32) -> Self {
+        Self {
+            wind_strength: self.wind_strength + (other.wind_strength - self.wind_strength) * t,
+            fog_density: self.fog_density + (other.fog_density - self.fog_density) * t,
+            rain_rate: self.rain_rate + (other.rain_rate - self.rain_rate) * t,
+        }
+    }
+}
+
+#[derive(Resource, Debug)]
+pub struct Weather {
+    // the weather that is setting in, or has set in once the transition is done
+    pub kind: WeatherKind,
+    pub current: WeatherConditions,
+    from: WeatherConditions,
+    transition: Timer,
+    // the kinds the weather cycles through on its own, a single entry keeps it fixed
+    pub presets: Vec<WeatherKind>,
+    pub change_timer: Timer,
+    // radians, 0 blows towards +x
+    pub wind_angle: f32,
+    // how far the wind has pushed the fog so far
+    pub wind_offset: Vec2,
+}
+
+impl Default for Weather {
+    fn default() -> Self {
+        let mut transition = Timer::from_seconds(WEATHER_TRANSITION_SECONDS, TimerMode::Once);
+        transition.tick(transition.duration());
+        Self {
+            kind: WeatherKind::Calm,
+            current: WeatherKind::Calm.conditions(),
+            from: WeatherKind::Calm.conditions(),
+            transition,
+            presets: vec![
+                WeatherKind::Calm,
+                WeatherKind::Breezy,
+                WeatherKind::Rain,
+                WeatherKind::Storm,
+            ],
+            change_timer: Timer::from_seconds(WEATHER_CHANGE_SECONDS, TimerMode::Repeating),
+            wind_angle: 0.0,
+            wind_offset: Vec2::ZERO,
+        }
+    }
+}
+
+impl Weather {
+    // eases from wherever the weather is right now, even halfway through another change
+    pub fn set(&mut self, kind: WeatherKind) {
+        if kind == self.kind {
+            return;
+        }
+        self.kind = kind;
+        self.from = self.current;
+        self.transition.reset();
+    }
+
+    // jumps straight to the kind without easing, for when a map starts
+    pub fn set_immediately(&mut self, kind: WeatherKind) {
+        self.set(kind);
+        self.current = kind.conditions();
+        self.transition.tick(self.transition.duration());
+    }
+
+    pub fn is_transitioning(&self) -> bool {
+        !self.transition.finished()
+    }
+
+    pub fn wind_direction(&self) -> Vec2 {
+        Vec2::from_angle(self.wind_angle)
+    }
+
+    pub fn water_animation_speed(&self) -> f32 {
+        1.0 + self.current.wind_strength * WEATHER_WATER_ANIMATION_WIND_SCALE
+    }
+}
+
+#[derive(Component)]
+pub struct RainDrop {
+    pub velocity: Vec2,
+    pub lifetime: Timer,
+}
+
+pub fn load_weather_from_map(
+    map_assets: Res<Assets<TiledMapSource>>,
+    tiled_asset: Res<TiledMapAssets>,
+    mut weather: ResMut<Weather>,
+) {
+    let Some(tiled_map) = map_assets.get(&tiled_asset.tiled_map) else {
+        return;
+    };
+    *weather = map_weather(&tiled_map.rs_tiled_map);
+}
+
+// map properties: `weather` is the kind the map starts with, `weather_presets` a comma separated
+// list of kinds it is allowed to change between and `wind_angle` the wind direction in degrees
+pub fn map_weather(map: &Map) -> Weather {
+    let mut weather = Weather::default();
+    let properties = &map.properties;
+
+    if let Some(presets) = weather_kinds_property(properties, "weather_presets") {
+        weather.presets = presets;
+    }
+    if let Some(angle) = float_property(properties, "wind_angle") {
+        weather.wind_angle = angle.to_radians();
+    }
+    let start = weather_kinds_property(properties, "weather")
+        .and_then(|kinds| kinds.first().copied())
+        .or_else(|| weather.presets.first().copied());
+    if let Some(start) = start {
+        weather.set_immediately(start);
+    }
+    weather
+}
+
+fn weather_kinds_property(properties: &Properties, name: &str) -> Option<Vec<WeatherKind>> {
+    let Some(PropertyValue::StringValue(value)) = properties.get(name) else {
+        return None;
+    };
+    let kinds: Vec<WeatherKind> = value
+        .split(',')
+        .filter_map(|kind_name| {
+            let kind = WeatherKind::from_name(kind_name);
+            if kind.is_none() {
+                info!(
+                    "Ignoring unknown weather {:?} in map property {}",
+                    kind_name, name
+                );
+            }
+            kind
+        })
+        .collect();
+    (!kinds.is_empty()).then_some(kinds)
+}
+
+// every so often the weather drifts to one of the map's presets, the pick comes out of GameRng
+// so this has to run in FixedUpdate
+pub fn pick_weather_changes(
+    time: Res<Time>,
+    mut game_rng: ResMut<GameRng>,
+    mut weather: ResMut<Weather>,
+) {
+    if weather.change_timer.tick(time.delta()).just_finished() && weather.presets.len() > 1 {
+        let next = weather.presets[game_rng.rng.gen_range(0..weather.presets.len())];
+        weather.set(next);
+    }
+}
+
+// eases over to whatever pick_weather_changes picked. fixed tick as well, replays checkpoint the
+// result
+pub fn update_weather(time: Res<Time>, mut weather: ResMut<Weather>) {
+    weather.transition.tick(time.delta());
+    let target = weather.kind.conditions();
+    let t = weather.transition.fraction();
+    // smoothstep so the change doesn't start or stop abruptly
+    weather.current = weather.from.lerp(&target, t * t * (3.0 - 2.0 * t));
+
+    let drift = weather.wind_direction()
+        * weather.current.wind_strength
+        * WEATHER_FOG_DRIFT_SPEED
+        * time.delta_seconds();
+    weather.wind_offset += drift;
+}
+
+// has to run after apply_time_of_day_to_fog, it scales the density that one picked
+pub fn apply_weather_to_fog(
+    weather: Res<Weather>,
+    mut fog_materials: ResMut<Assets<FogMaterial>>,
+    mut water_materials: ResMut<Assets<WaterMaterial>>,
+) {
+    let wind_direction = weather.wind_direction();
+    for fog in tilemap_fogs(&mut fog_materials, &mut water_materials) {
+        fog.density = (fog.density * weather.current.fog_density).min(1.0);
+        fog.wind_dir = Vec3::new(wind_direction.x, wind_direction.y, 0.0);
+        fog.wind_offset = weather.wind_offset.extend(0.0);
+    }
+}
+
+// drops start along the top of every camera's view, so both screens get rained on
+pub fn spawn_rain_drops(
+    mut commands: Commands,
+    time: Res<Time>,
+    weather: Res<Weather>,
+    mut effects_rng: ResMut<EffectsRng>,
+    mut pending_drops: Local<f32>,
+    camera_query: Query<(&GlobalTransform, &OrthographicProjection), With<Camera>>,
+) {
+    let views = camera_views(&camera_query);
+    *pending_drops += weather.current.rain_rate * time.delta_seconds() * views.len() as f32;
+    if views.is_empty() || weather.current.rain_rate <= 0.0 {
+        *pending_drops = 0.0;
+        return;
+    }
+
+    let velocity = Vec2::new(
+        weather.wind_direction().x * weather.current.wind_strength * RAIN_DROP_WIND_PUSH,
+        -RAIN_DROP_FALL_SPEED,
+    );
+    // the streak leans with the wind
+    let rotation = Quat::from_rotation_z(velocity.to_angle() + FRAC_PI_2);
+
+    while *pending_drops >= 1.0 {
+        *pending_drops -= 1.0;
+        let view = views[effects_rng.0.gen_range(0..views.len())];
+        let x = effects_rng.0.gen_range(view.min.x..=view.max.x);
+        let lifetime = view.height() / RAIN_DROP_FALL_SPEED * effects_rng.0.gen_range(0.5..=1.0);
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::srgba(0.75, 0.85, 1.0, 0.6),
+                    custom_size: Some(Vec2::new(2.0, RAIN_DROP_LENGTH)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(x, view.max.y, RAIN_Z_LEVEL).with_rotation(rotation),
+                ..default()
+            },
+            RainDrop {
+                velocity,
+                lifetime: Timer::from_seconds(lifetime, TimerMode::Once),
+            },
+        ));
+    }
+}
+
+// what each camera sees in world space, grown by a tile so drops start just above the edge
+fn camera_views(
+    camera_query: &Query<(&GlobalTransform, &OrthographicProjection), With<Camera>>,
+) -> Vec<Rect> {
+    camera_query
+        .iter()
+        .map(|(camera_transform, projection)| {
+            camera_view(camera_transform, projection).inflate(TILE_SIZE)
+        })
+        .collect()
+}
+
+pub fn fall_rain_drops(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Transform, &mut RainDrop)>,
+) {
+    for (entity, mut transform, mut rain_drop) in query.iter_mut() {
+        transform.translation += (rain_drop.velocity * time.delta_seconds()).extend(0.0);
+        if rain_drop.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
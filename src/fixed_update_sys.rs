@@ -0,0 +1,60 @@
+use bevy::{
+    ecs::schedule::SystemConfigs,
+    prelude::{resource_exists, IntoSystemConfigs},
+};
+
+use crate::{
+    ai::{
+        ai_sys::run_steering_agents,
+        flock_sys::run_flocking,
+        path_sys::{
+            follow_paths, queue_path_requests, request_seek_paths, run_path_searches,
+            update_pathfinding_grid,
+        },
+    },
+    environment::{
+        day_night::advance_time_of_day,
+        environment_sys::{despawn_offscreen_environment_entities, run_environment_spawners},
+        moon_phase::update_lunar_phase,
+        weather::{pick_weather_changes, update_weather},
+    },
+    kinetic_sys::{integrate_kinetic_entities, resolve_tile_collisions},
+    player::player_sys::control_player_entity,
+    replay::{
+        replay_res::{ReplayPlayback, ReplayRecorder},
+        replay_sys::{
+            play_replay_tick, record_replay_checkpoint, record_replay_tick, report_replay_result,
+        },
+    },
+};
+
+// everything gameplay does on one fixed tick, in order. shared by main and the replay test so a
+// replay always runs through exactly what the game runs
+pub fn fixed_update_systems() -> SystemConfigs {
+    (
+        play_replay_tick.run_if(resource_exists::<ReplayPlayback>),
+        record_replay_tick.run_if(resource_exists::<ReplayRecorder>),
+        advance_time_of_day,
+        update_lunar_phase,
+        control_player_entity,
+        (
+            update_pathfinding_grid,
+            run_steering_agents,
+            run_flocking,
+            request_seek_paths,
+            queue_path_requests,
+            run_path_searches,
+            follow_paths,
+        )
+            .chain(),
+        integrate_kinetic_entities,
+        resolve_tile_collisions,
+        run_environment_spawners,
+        despawn_offscreen_environment_entities,
+        pick_weather_changes,
+        update_weather,
+        record_replay_checkpoint.run_if(resource_exists::<ReplayRecorder>),
+        report_replay_result.run_if(resource_exists::<ReplayPlayback>),
+    )
+        .chain()
+}
@@ -0,0 +1,129 @@
+use bevy::{
+    input::{
+        gamepad::{GamepadAxisType, GamepadButtonType},
+        keyboard::KeyCode,
+    },
+    math::Vec2,
+    prelude::Component,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::kinetic_components::SteeringMode;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ButtonAction {
+    pub pressed: bool,
+    // latched until a fixed tick consumes it, otherwise frames without a fixed tick would eat presses
+    pub just_pressed: bool,
+}
+
+// what the entity is being asked to do this tick, gameplay only ever reads this and never devices
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct InputActions {
+    pub move_axis: Vec2,
+    pub dash: ButtonAction,
+    pub dive: ButtonAction,
+    // defaulted so replays recorded before jumping existed still load
+    #[serde(default)]
+    pub jump: ButtonAction,
+}
+
+impl InputActions {
+    pub fn consume_just_pressed(&mut self) {
+        self.dash.just_pressed = false;
+        self.dive.just_pressed = false;
+        self.jump.just_pressed = false;
+    }
+}
+
+// the entity the stylus/mouse on the bottom screen steers, DS style
+#[derive(Component, Default)]
+pub struct PointerSteeringTag;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GamepadBindings {
+    pub gamepad_id: usize,
+    pub move_x: GamepadAxisType,
+    pub move_y: GamepadAxisType,
+    pub dead_zone: f32,
+    pub dash: Vec<GamepadButtonType>,
+    pub dive: Vec<GamepadButtonType>,
+    #[serde(default = "default_gamepad_jump_buttons")]
+    pub jump: Vec<GamepadButtonType>,
+}
+
+// entities without bindings keep whatever actions get written into them, which is how tests and
+// replays drive the player without real devices
+#[derive(Component, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InputBindings {
+    pub move_up: Vec<KeyCode>,
+    pub move_down: Vec<KeyCode>,
+    pub move_left: Vec<KeyCode>,
+    pub move_right: Vec<KeyCode>,
+    pub dash: Vec<KeyCode>,
+    pub dive: Vec<KeyCode>,
+    #[serde(default = "default_jump_keys")]
+    pub jump: Vec<KeyCode>,
+    pub gamepad: Option<GamepadBindings>,
+    // how this player's move axes get read, see SteeringMode
+    #[serde(default)]
+    pub steering_mode: SteeringMode,
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        Self {
+            move_up: vec![KeyCode::KeyW],
+            move_down: vec![KeyCode::KeyS],
+            move_left: vec![KeyCode::KeyA],
+            move_right: vec![KeyCode::KeyD],
+            dash: vec![KeyCode::ShiftLeft],
+            dive: vec![KeyCode::Space],
+            jump: default_jump_keys(),
+            gamepad: Some(GamepadBindings {
+                gamepad_id: 0,
+                move_x: GamepadAxisType::LeftStickX,
+                move_y: GamepadAxisType::LeftStickY,
+                dead_zone: 0.15,
+                dash: vec![GamepadButtonType::East],
+                dive: vec![GamepadButtonType::South],
+                jump: default_gamepad_jump_buttons(),
+            }),
+            steering_mode: SteeringMode::default(),
+        }
+    }
+}
+
+impl InputBindings {
+    // arrow keys on the right hand side of the keyboard plus the second gamepad
+    pub fn player_two() -> Self {
+        Self {
+            move_up: vec![KeyCode::ArrowUp],
+            move_down: vec![KeyCode::ArrowDown],
+            move_left: vec![KeyCode::ArrowLeft],
+            move_right: vec![KeyCode::ArrowRight],
+            dash: vec![KeyCode::ShiftRight],
+            dive: vec![KeyCode::Enter],
+            jump: vec![KeyCode::ControlRight],
+            gamepad: Some(GamepadBindings {
+                gamepad_id: 1,
+                move_x: GamepadAxisType::LeftStickX,
+                move_y: GamepadAxisType::LeftStickY,
+                dead_zone: 0.15,
+                dash: vec![GamepadButtonType::East],
+                dive: vec![GamepadButtonType::South],
+                jump: default_gamepad_jump_buttons(),
+            }),
+            steering_mode: SteeringMode::default(),
+        }
+    }
+}
+
+// config files written before jumping existed don't have these
+fn default_jump_keys() -> Vec<KeyCode> {
+    vec![KeyCode::KeyJ]
+}
+
+fn default_gamepad_jump_buttons() -> Vec<GamepadButtonType> {
+    vec![GamepadButtonType::North]
+}
@@ -0,0 +1,57 @@
+use std::{fs, io, path::Path};
+
+use bevy::{math::Vec2, prelude::Resource};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::input::input_components::InputBindings;
+
+// one set of bindings per local player, index 0 is player one
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InputConfig {
+    pub players: Vec<InputBindings>,
+}
+
+impl Default for InputConfig {
+    fn default() -> Self {
+        Self {
+            players: vec![InputBindings::default(), InputBindings::player_two()],
+        }
+    }
+}
+
+impl InputConfig {
+    pub fn load(path: &Path) -> Result<Self, InputConfigError> {
+        let contents = fs::read_to_string(path)?;
+        ron::from_str(&contents).map_err(|e| InputConfigError::Parse(e.to_string()))
+    }
+
+    // writes the bindings back out in the same format load reads, e.g. after rebinding in game
+    pub fn save(&self, path: &Path) -> Result<(), InputConfigError> {
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| InputConfigError::Serialize(e.to_string()))?;
+        fs::write(path, contents)?;
+        Ok(())
+    }
+}
+
+// mouse or touch on the bottom screen, already converted to world coordinates
+#[derive(Resource, Default, Debug)]
+pub struct PointerState {
+    pub held: bool,
+    pub world_position: Option<Vec2>,
+    pub press_world_position: Option<Vec2>,
+    pub press_seconds: f32,
+}
+
+#[derive(Error, Debug)]
+pub enum InputConfigError {
+    #[error("IO Error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Input Config Parsing Error: {0}")]
+    Parse(String),
+
+    #[error("Input Config Serializing Error: {0}")]
+    Serialize(String),
+}
@@ -0,0 +1,231 @@
+use std::{io::ErrorKind, path::Path};
+
+use bevy::{
+    input::{
+        gamepad::{Gamepad, GamepadAxis, GamepadButton},
+        keyboard::KeyCode,
+        mouse::MouseButton,
+        touch::Touches,
+        Axis, ButtonInput,
+    },
+    log::{info, warn},
+    math::Vec2,
+    prelude::{
+        Camera, Commands, Event, EventWriter, GlobalTransform, Query, Res, ResMut, Time, Window,
+        With,
+    },
+    window::PrimaryWindow,
+};
+
+use crate::{
+    camera::camera_components::BottomCameraTag,
+    input::{
+        input_components::{ButtonAction, InputActions, InputBindings, PointerSteeringTag},
+        input_res::{InputConfig, InputConfigError, PointerState},
+    },
+    kinetic_components::{KineticEntityComponents, SteeringMode},
+    INPUT_CONFIG_FILE_PATH, STYLUS_ARRIVE_RADIUS, STYLUS_TAP_MAX_DISTANCE, STYLUS_TAP_MAX_SECONDS,
+};
+
+// a quick tap on the bottom screen, like poking the DS touch screen with the stylus
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PokeEvent {
+    pub world_position: Vec2,
+}
+
+// the default bindings ship in config/, a missing file just means playing on the built in ones
+pub fn load_input_config(mut commands: Commands) {
+    let path = Path::new(INPUT_CONFIG_FILE_PATH);
+    let input_config = match InputConfig::load(path) {
+        Ok(input_config) => input_config,
+        Err(InputConfigError::Io(e)) if e.kind() == ErrorKind::NotFound => {
+            info!("No input config at {:?}, using the default bindings", path);
+            InputConfig::default()
+        }
+        Err(e) => {
+            warn!(
+                "Failed to load input config, using the default bindings: {}",
+                e
+            );
+            InputConfig::default()
+        }
+    };
+    commands.insert_resource(input_config);
+}
+
+pub fn gather_input_actions(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    mut query: Query<(&InputBindings, &mut InputActions)>,
+) {
+    for (bindings, mut actions) in query.iter_mut() {
+        let keyboard_axis = Vec2::new(
+            axis_from_keys(&keyboard_input, &bindings.move_right, &bindings.move_left),
+            axis_from_keys(&keyboard_input, &bindings.move_up, &bindings.move_down),
+        );
+        let mut move_axis = keyboard_axis;
+        let mut dash = button_from_keys(&keyboard_input, &bindings.dash);
+        let mut dive = button_from_keys(&keyboard_input, &bindings.dive);
+        let mut jump = button_from_keys(&keyboard_input, &bindings.jump);
+
+        if let Some(gamepad_bindings) = &bindings.gamepad {
+            let gamepad = Gamepad::new(gamepad_bindings.gamepad_id);
+            let stick = Vec2::new(
+                gamepad_axes
+                    .get(GamepadAxis::new(gamepad, gamepad_bindings.move_x))
+                    .unwrap_or(0.0),
+                gamepad_axes
+                    .get(GamepadAxis::new(gamepad, gamepad_bindings.move_y))
+                    .unwrap_or(0.0),
+            );
+            if stick.length() > gamepad_bindings.dead_zone {
+                move_axis += stick;
+            }
+            for button_type in gamepad_bindings.dash.iter() {
+                let button = GamepadButton::new(gamepad, *button_type);
+                dash.pressed |= gamepad_buttons.pressed(button);
+                dash.just_pressed |= gamepad_buttons.just_pressed(button);
+            }
+            for button_type in gamepad_bindings.dive.iter() {
+                let button = GamepadButton::new(gamepad, *button_type);
+                dive.pressed |= gamepad_buttons.pressed(button);
+                dive.just_pressed |= gamepad_buttons.just_pressed(button);
+            }
+            for button_type in gamepad_bindings.jump.iter() {
+                let button = GamepadButton::new(gamepad, *button_type);
+                jump.pressed |= gamepad_buttons.pressed(button);
+                jump.just_pressed |= gamepad_buttons.just_pressed(button);
+            }
+        }
+
+        // keyboard and stick together can overshoot, each axis is clamped on its own so tank
+        // steering keeps a full turn and full throttle at the same time
+        actions.move_axis = move_axis.clamp(Vec2::NEG_ONE, Vec2::ONE);
+        actions.dash = ButtonAction {
+            pressed: dash.pressed,
+            just_pressed: actions.dash.just_pressed || dash.just_pressed,
+        };
+        actions.dive = ButtonAction {
+            pressed: dive.pressed,
+            just_pressed: actions.dive.just_pressed || dive.just_pressed,
+        };
+        actions.jump = ButtonAction {
+            pressed: jump.pressed,
+            just_pressed: actions.jump.just_pressed || jump.just_pressed,
+        };
+    }
+}
+
+fn axis_from_keys(
+    keyboard_input: &ButtonInput<KeyCode>,
+    positive: &[KeyCode],
+    negative: &[KeyCode],
+) -> f32 {
+    let positive = keyboard_input.any_pressed(positive.iter().copied()) as i32;
+    let negative = keyboard_input.any_pressed(negative.iter().copied()) as i32;
+    (positive - negative) as f32
+}
+
+fn button_from_keys(keyboard_input: &ButtonInput<KeyCode>, keys: &[KeyCode]) -> ButtonAction {
+    ButtonAction {
+        pressed: keyboard_input.any_pressed(keys.iter().copied()),
+        just_pressed: keyboard_input.any_just_pressed(keys.iter().copied()),
+    }
+}
+
+pub fn track_bottom_screen_pointer(
+    time: Res<Time>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    touches: Res<Touches>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<BottomCameraTag>>,
+    mut pointer: ResMut<PointerState>,
+    mut poke_writer: EventWriter<PokeEvent>,
+) {
+    let touch_position = touches.first_pressed_position();
+    let pressed = mouse_buttons.pressed(MouseButton::Left) || touch_position.is_some();
+    let screen_position = touch_position.or_else(|| {
+        window_query
+            .iter()
+            .next()
+            .and_then(|window| window.cursor_position())
+    });
+
+    let world_position = screen_position.and_then(|screen_position| {
+        camera_query.iter().find_map(|(camera, camera_transform)| {
+            let viewport = camera.logical_viewport_rect()?;
+            if !viewport.contains(screen_position) {
+                return None;
+            }
+            camera.viewport_to_world_2d(camera_transform, screen_position - viewport.min)
+        })
+    });
+
+    let now = time.elapsed_seconds();
+    if pressed && !pointer.held {
+        // presses that start outside of the bottom screen are ignored entirely
+        if world_position.is_none() {
+            return;
+        }
+        pointer.held = true;
+        pointer.press_seconds = now;
+        pointer.press_world_position = world_position;
+    } else if !pressed && pointer.held {
+        pointer.held = false;
+        let quick = now - pointer.press_seconds <= STYLUS_TAP_MAX_SECONDS;
+        if let (true, Some(press_position)) = (quick, pointer.press_world_position) {
+            let release_position = world_position.or(pointer.world_position);
+            let still = release_position.is_none_or(|release_position| {
+                release_position.distance(press_position) <= STYLUS_TAP_MAX_DISTANCE
+            });
+            if still {
+                poke_writer.send(PokeEvent {
+                    world_position: press_position,
+                });
+            }
+        }
+    }
+
+    // dragging off the bottom screen keeps steering towards the last point that was on it
+    if pointer.held && world_position.is_some() {
+        pointer.world_position = world_position;
+    }
+}
+
+// runs after gather_input_actions, a held pointer overrides whatever the devices said
+pub fn steer_towards_pointer(
+    pointer: Res<PointerState>,
+    mut query: Query<
+        (
+            &KineticEntityComponents,
+            &mut InputActions,
+            Option<&SteeringMode>,
+        ),
+        With<PointerSteeringTag>,
+    >,
+) {
+    let (true, Some(target)) = (pointer.held, pointer.world_position) else {
+        return;
+    };
+    for (kinetics, mut actions, steering_mode) in query.iter_mut() {
+        let to_target = target - kinetics.position.truncate();
+        if to_target.length() <= STYLUS_ARRIVE_RADIUS {
+            actions.move_axis = Vec2::ZERO;
+            continue;
+        }
+        let desired_direction = to_target.normalize();
+        actions.move_axis = match steering_mode.copied().unwrap_or_default() {
+            SteeringMode::Strafe => desired_direction,
+            SteeringMode::Tank => {
+                // turn towards the point and only throttle once it is roughly in front
+                let heading_direction = Vec2::from_angle(kinetics.heading);
+                let angle = heading_direction.angle_between(desired_direction);
+                Vec2::new(
+                    -(angle / std::f32::consts::FRAC_PI_2).clamp(-1.0, 1.0),
+                    angle.cos().max(0.0),
+                )
+            }
+        };
+    }
+}
@@ -0,0 +1,3 @@
+pub mod input_components;
+pub mod input_res;
+pub mod input_sys;
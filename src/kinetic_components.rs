@@ -1,4 +1,10 @@
-use bevy::{math::Vec3, prelude::Component};
+use bevy::{
+    math::{Vec2, Vec3},
+    prelude::{Component, Entity},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::DEPTH_SURFACE_THRESHOLD;
 
 #[derive(Component)]
 pub struct KineticEntityComponents {
@@ -6,10 +12,89 @@ pub struct KineticEntityComponents {
     pub x_axis_displacement: f32,
     pub position: Vec3,
     pub prev_position: Vec3,
+    // radians, counter clockwise from +x
+    pub heading: f32,
+}
+
+impl KineticEntityComponents {
+    // where the entity is between the last two fixed ticks, this is where it gets drawn. anything
+    // that should follow the entity itself rather than its sprite (which hops during a jump)
+    // should read this instead of the Transform
+    pub fn interpolated_position(&self, overstep_fraction: f32) -> Vec3 {
+        self.prev_position.lerp(self.position, overstep_fraction)
+    }
+}
+
+// the raw steering axes for the next fixed tick, written by whatever drives the entity
+// (player input, AI) and read by integrate_kinetic_entities, see swim_physics_step for the meaning
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct SteeringInput(pub Vec2);
+
+// gets pushed back out of solid tiles after every fixed tick
+#[derive(Component, Default)]
+pub struct TileCollider;
+
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SteeringMode {
+    // 8 way movement, the input is the direction to swim in
+    #[default]
+    Strafe,
+    // A/D rotate the heading and W/S thrust forwards/backwards along it
+    Tank,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DepthLevel {
+    #[default]
+    Surface,
+    Underwater,
+}
+
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct DepthComponent {
+    // where the entity is heading to
+    pub level: DepthLevel,
+    // where it actually is, 0.0 at the surface and 1.0 fully underwater
+    pub depth: f32,
+}
+
+impl DepthComponent {
+    pub fn at_surface(&self) -> bool {
+        self.depth < DEPTH_SURFACE_THRESHOLD
+    }
+}
+
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct JumpComponent {
+    // above the water plane, 0.0 while swimming
+    pub height: f32,
+    pub vertical_velocity: f32,
+}
+
+impl JumpComponent {
+    pub fn is_airborne(&self) -> bool {
+        self.height > 0.0 || self.vertical_velocity > 0.0
+    }
+}
+
+// the dark blob that stays on the water plane underneath a jumping entity
+#[derive(Component)]
+pub struct JumpShadow {
+    pub target: Entity,
+}
+
+// the upside down, faded copy of an entity drawn on the water underneath it
+#[derive(Component)]
+pub struct WaterReflection {
+    pub target: Entity,
 }
 
 #[derive(Component)]
 pub struct PlayerEntityTag;
 
+// 0 is player one, the one the bottom screen belongs to
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PlayerSlot(pub usize);
+
 #[derive(Component)]
 pub struct EnvironmentEntityTag;
@@ -0,0 +1,62 @@
+use bevy::prelude::Resource;
+
+use crate::DEFAULT_SPEED;
+
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct SwimPhysicsSettings {
+    // units per second squared gained while thrusting
+    pub acceleration: f32,
+    // fraction of velocity the water eats per second
+    pub drag: f32,
+    pub max_speed: f32,
+    // radians per second the heading can swing towards the input direction
+    pub turn_rate: f32,
+    // radians per second the heading rotates in SteeringMode::Tank
+    pub tank_turn_rate: f32,
+    // below this the entity is considered to be at rest and snaps to zero
+    pub rest_speed: f32,
+}
+
+impl Default for SwimPhysicsSettings {
+    fn default() -> Self {
+        Self {
+            acceleration: 600.0,
+            drag: 2.5,
+            max_speed: DEFAULT_SPEED,
+            turn_rate: 6.0,
+            tank_turn_rate: 3.0,
+            rest_speed: 2.0,
+        }
+    }
+}
+
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct JumpSettings {
+    // seconds from leaving the water to splashing back down
+    pub airtime: f32,
+    // world units above the water plane at the top of the arc
+    pub apex_height: f32,
+    // how much bigger the sprite gets at the apex, 0.5 is 150%
+    pub apex_scale_bonus: f32,
+}
+
+impl Default for JumpSettings {
+    fn default() -> Self {
+        Self {
+            airtime: 0.8,
+            apex_height: 48.0,
+            apex_scale_bonus: 0.5,
+        }
+    }
+}
+
+impl JumpSettings {
+    // a ballistic arc that peaks at apex_height halfway through the airtime
+    pub fn launch_velocity(&self) -> f32 {
+        4.0 * self.apex_height / self.airtime
+    }
+
+    pub fn gravity(&self) -> f32 {
+        8.0 * self.apex_height / (self.airtime * self.airtime)
+    }
+}
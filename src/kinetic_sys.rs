@@ -0,0 +1,427 @@
+use bevy::{
+    asset::Handle,
+    color::Color,
+    core::Name,
+    math::{Quat, Vec2, Vec3},
+    prelude::{
+        Added, Commands, Entity, Event, Fixed, Image, Or, Query, Res, Sprite, TextureAtlas, Time,
+        Transform, Visibility, With, Without,
+    },
+    sprite::SpriteBundle,
+};
+
+use crate::{
+    kinetic_components::{
+        DepthComponent, DepthLevel, JumpComponent, JumpShadow, KineticEntityComponents,
+        SteeringInput, SteeringMode, TileCollider, WaterReflection,
+    },
+    kinetic_res::{JumpSettings, SwimPhysicsSettings},
+    map::tiled_res::TileCollisionMap,
+    DEPTH_SURFACE_THRESHOLD, DIVE_SPEED, JUMP_SHADOW_ALPHA, JUMP_SHADOW_Z_LEVEL, REFLECTION_ALPHA,
+    REFLECTION_OFFSET, REFLECTION_SQUASH, REFLECTION_TINT, REFLECTION_WOBBLE,
+    REFLECTION_WOBBLE_SPEED, REFLECTION_Z_LEVEL, UNDERWATER_SCALE, UNDERWATER_TINT,
+};
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct SplashEvent {
+    pub entity: Entity,
+    pub position: Vec3,
+}
+
+// one fixed tick of swimming: turn the steering input into a thrust direction, steer the velocity
+// towards it with a limited turn rate, accelerate along it, let the water drag it down, then move
+//
+// steering_input is the raw (x, y) input axes, whose meaning depends on the steering mode:
+//  Strafe: the direction to swim in
+//  Tank:   x rotates the heading (positive is clockwise), y is the throttle along the heading
+pub fn swim_physics_step(
+    kinetics: &mut KineticEntityComponents,
+    steering_mode: SteeringMode,
+    steering_input: Vec2,
+    settings: &SwimPhysicsSettings,
+    delta_seconds: f32,
+) {
+    let thrust_direction = match steering_mode {
+        // normalized so diagonals are not √2 faster
+        SteeringMode::Strafe => steering_input.normalize_or_zero(),
+        SteeringMode::Tank => {
+            kinetics.heading -= steering_input.x * settings.tank_turn_rate * delta_seconds;
+            Vec2::from_angle(kinetics.heading) * steering_input.y.clamp(-1.0, 1.0)
+        }
+    };
+
+    let mut velocity = Vec2::new(kinetics.x_axis_displacement, kinetics.y_axis_displacement);
+
+    if thrust_direction != Vec2::ZERO {
+        let speed = velocity.length();
+        let thrust = settings.acceleration * delta_seconds * thrust_direction.length();
+        if speed > settings.rest_speed {
+            let current_direction = velocity / speed;
+            let max_turn = settings.turn_rate * delta_seconds;
+            let turn = current_direction
+                .angle_between(thrust_direction)
+                .clamp(-max_turn, max_turn);
+            velocity = Vec2::from_angle(turn).rotate(current_direction) * (speed + thrust);
+        } else {
+            velocity = thrust_direction.normalize() * (speed + thrust);
+        }
+    }
+
+    velocity *= (-settings.drag * delta_seconds).exp();
+    velocity = velocity.clamp_length_max(settings.max_speed);
+    if thrust_direction == Vec2::ZERO && velocity.length() < settings.rest_speed {
+        velocity = Vec2::ZERO;
+    }
+
+    // in strafe mode the heading just follows wherever the entity is actually swimming
+    if steering_mode == SteeringMode::Strafe && velocity.length() > settings.rest_speed {
+        kinetics.heading = velocity.y.atan2(velocity.x);
+    }
+
+    kinetics.x_axis_displacement = velocity.x;
+    kinetics.y_axis_displacement = velocity.y;
+    kinetics.prev_position = kinetics.position;
+    kinetics.position += Vec3::new(velocity.x, velocity.y, 0.0) * delta_seconds;
+}
+
+// every kinetic entity swims one fixed tick, entities without a SteeringInput just coast to a stop
+pub fn integrate_kinetic_entities(
+    fixed_time: Res<Time<Fixed>>,
+    swim_physics_settings: Res<SwimPhysicsSettings>,
+    mut query: Query<(
+        &mut KineticEntityComponents,
+        Option<&SteeringMode>,
+        Option<&SteeringInput>,
+    )>,
+) {
+    for (mut kinetics, steering_mode, steering_input) in query.iter_mut() {
+        swim_physics_step(
+            &mut kinetics,
+            steering_mode.copied().unwrap_or_default(),
+            steering_input.map_or(Vec2::ZERO, |steering_input| steering_input.0),
+            &swim_physics_settings,
+            fixed_time.delta_seconds(),
+        );
+    }
+}
+
+// has to run in FixedUpdate after integrate_kinetic_entities, slides along walls one axis at a time.
+// jumping entities sail over solid tiles, and one that landed inside of one is let swim back out
+pub fn resolve_tile_collisions(
+    collision_map: Option<Res<TileCollisionMap>>,
+    mut query: Query<(&mut KineticEntityComponents, Option<&JumpComponent>), With<TileCollider>>,
+) {
+    let Some(collision_map) = collision_map else {
+        return;
+    };
+    for (mut kinetics, jump) in query.iter_mut() {
+        if jump.is_some_and(|jump| jump.is_airborne())
+            || collision_map.is_walkable_at(&kinetics.position)
+            || !collision_map.is_walkable_at(&kinetics.prev_position)
+        {
+            continue;
+        }
+        let position = kinetics.position;
+        let prev_position = kinetics.prev_position;
+        let x_only = Vec3::new(position.x, prev_position.y, position.z);
+        let y_only = Vec3::new(prev_position.x, position.y, position.z);
+        if collision_map.is_walkable_at(&x_only) {
+            kinetics.position = x_only;
+            kinetics.y_axis_displacement = 0.0;
+        } else if collision_map.is_walkable_at(&y_only) {
+            kinetics.position = y_only;
+            kinetics.x_axis_displacement = 0.0;
+        } else {
+            kinetics.position = prev_position;
+            kinetics.x_axis_displacement = 0.0;
+            kinetics.y_axis_displacement = 0.0;
+        }
+    }
+}
+
+// frames render between fixed ticks, so the sprite is placed between the last two simulated positions
+pub fn interpolate_kinetic_transforms(
+    fixed_time: Res<Time<Fixed>>,
+    mut query: Query<(
+        &KineticEntityComponents,
+        &mut Transform,
+        Option<&SteeringMode>,
+        Option<&JumpComponent>,
+    )>,
+) {
+    let a = fixed_time.overstep_fraction();
+    for (kinetics, mut transform, steering_mode, jump) in query.iter_mut() {
+        transform.translation = kinetics.interpolated_position(a);
+        // airborne sprites get lifted by their jump height, their shadow stays on the water
+        if let Some(jump) = jump {
+            transform.translation.y += jump.height;
+        }
+        if steering_mode == Some(&SteeringMode::Tank) {
+            transform.rotation = Quat::from_rotation_z(kinetics.heading);
+        }
+    }
+}
+
+// flips between the surface and underwater when asked to, then eases the depth towards the level
+pub fn dive_step(depth: &mut DepthComponent, toggle_dive: bool, delta_seconds: f32) {
+    if toggle_dive {
+        depth.level = match depth.level {
+            DepthLevel::Surface => DepthLevel::Underwater,
+            DepthLevel::Underwater => DepthLevel::Surface,
+        };
+    }
+    let target_depth = match depth.level {
+        DepthLevel::Surface => 0.0,
+        DepthLevel::Underwater => 1.0,
+    };
+    let max_step = DIVE_SPEED * delta_seconds;
+    depth.depth += (target_depth - depth.depth).clamp(-max_step, max_step);
+}
+
+// returns true on the tick the entity splashes back down into the water
+pub fn jump_step(
+    jump: &mut JumpComponent,
+    launch: bool,
+    settings: &JumpSettings,
+    delta_seconds: f32,
+) -> bool {
+    if launch && !jump.is_airborne() {
+        jump.vertical_velocity = settings.launch_velocity();
+    }
+    if !jump.is_airborne() {
+        return false;
+    }
+    jump.vertical_velocity -= settings.gravity() * delta_seconds;
+    jump.height += jump.vertical_velocity * delta_seconds;
+    if jump.height <= 0.0 {
+        jump.height = 0.0;
+        jump.vertical_velocity = 0.0;
+        return true;
+    }
+    false
+}
+
+// the sprite side of an entity that can dive or jump
+type DepthAppearanceData = (
+    Option<&'static DepthComponent>,
+    Option<&'static JumpComponent>,
+    &'static mut Sprite,
+    &'static mut Transform,
+);
+type DepthAppearanceFilter = Or<(With<DepthComponent>, With<JumpComponent>)>;
+
+pub fn apply_depth_and_jump_appearance(
+    jump_settings: Res<JumpSettings>,
+    mut query: Query<DepthAppearanceData, DepthAppearanceFilter>,
+) {
+    for (depth, jump, mut sprite, mut transform) in query.iter_mut() {
+        let depth = depth.map_or(0.0, |depth| depth.depth);
+        let tint = Vec3::ONE.lerp(UNDERWATER_TINT, depth);
+        sprite.color = Color::srgb(tint.x, tint.y, tint.z);
+
+        let depth_scale = 1.0 + (UNDERWATER_SCALE - 1.0) * depth;
+        let jump_scale = jump.map_or(1.0, |jump| {
+            1.0 + jump_settings.apex_scale_bonus * jump.height / jump_settings.apex_height
+        });
+        transform.scale = Vec3::splat(depth_scale * jump_scale);
+    }
+}
+
+// the shadow is a darkened copy of whatever the jumping entity looks like
+type JumpShadowSource = (
+    Entity,
+    &'static Handle<Image>,
+    Option<&'static TextureAtlas>,
+);
+
+pub fn spawn_jump_shadows(
+    mut commands: Commands,
+    query: Query<JumpShadowSource, Added<JumpComponent>>,
+) {
+    for (target, texture, texture_atlas) in query.iter() {
+        let mut shadow = commands.spawn(SpriteBundle {
+            texture: texture.clone(),
+            sprite: Sprite {
+                color: Color::srgba(0.0, 0.0, 0.0, JUMP_SHADOW_ALPHA),
+                ..Default::default()
+            },
+            visibility: Visibility::Hidden,
+            ..Default::default()
+        });
+        if let Some(texture_atlas) = texture_atlas {
+            shadow.insert(texture_atlas.clone());
+        }
+        shadow
+            .insert(JumpShadow { target })
+            .insert(Name::new("JumpShadow"));
+    }
+}
+
+type JumpShadowData = (
+    Entity,
+    &'static JumpShadow,
+    &'static mut Transform,
+    &'static mut Visibility,
+    Option<&'static mut TextureAtlas>,
+);
+
+pub fn sync_jump_shadows(
+    mut commands: Commands,
+    jump_settings: Res<JumpSettings>,
+    target_query: Query<(&Transform, &JumpComponent, Option<&TextureAtlas>), Without<JumpShadow>>,
+    mut shadow_query: Query<JumpShadowData>,
+) {
+    for (shadow_entity, shadow, mut shadow_transform, mut visibility, shadow_atlas) in
+        shadow_query.iter_mut()
+    {
+        let Ok((target_transform, jump, target_atlas)) = target_query.get(shadow.target) else {
+            commands.entity(shadow_entity).despawn();
+            continue;
+        };
+
+        // the target sprite gets lifted by its height, the shadow stays down on the water
+        shadow_transform.translation = Vec3::new(
+            target_transform.translation.x,
+            target_transform.translation.y - jump.height,
+            JUMP_SHADOW_Z_LEVEL,
+        );
+        shadow_transform.rotation = target_transform.rotation;
+        shadow_transform.scale =
+            Vec3::splat(1.0 - 0.5 * (jump.height / jump_settings.apex_height).min(1.0));
+
+        if let (Some(mut shadow_atlas), Some(target_atlas)) = (shadow_atlas, target_atlas) {
+            shadow_atlas.index = target_atlas.index;
+        }
+
+        *visibility = if jump.is_airborne() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+// like the jump shadow, the reflection is a copy of whatever the entity looks like
+type WaterReflectionSource = (
+    Entity,
+    &'static Handle<Image>,
+    Option<&'static TextureAtlas>,
+);
+
+pub fn spawn_water_reflections(
+    mut commands: Commands,
+    query: Query<WaterReflectionSource, Added<KineticEntityComponents>>,
+) {
+    for (target, texture, texture_atlas) in query.iter() {
+        let mut reflection = commands.spawn(SpriteBundle {
+            texture: texture.clone(),
+            visibility: Visibility::Hidden,
+            ..Default::default()
+        });
+        if let Some(texture_atlas) = texture_atlas {
+            reflection.insert(texture_atlas.clone());
+        }
+        reflection
+            .insert(WaterReflection { target })
+            .insert(Name::new("WaterReflection"));
+    }
+}
+
+type WaterReflectionTarget = (
+    &'static Transform,
+    &'static Sprite,
+    &'static Visibility,
+    &'static KineticEntityComponents,
+    Option<&'static DepthComponent>,
+    Option<&'static JumpComponent>,
+    Option<&'static TextureAtlas>,
+);
+
+type WaterReflectionData = (
+    Entity,
+    &'static WaterReflection,
+    &'static mut Transform,
+    &'static mut Sprite,
+    &'static mut Visibility,
+    Option<&'static mut TextureAtlas>,
+);
+
+// mirrored about the spot on the water the entity is over, so the higher it jumps the further down
+// its reflection goes. only open water reflects, going by the same collision map the swimming uses,
+// and nothing gets reflected once it's under the surface
+pub fn sync_water_reflections(
+    mut commands: Commands,
+    time: Res<Time>,
+    jump_settings: Res<JumpSettings>,
+    collision_map: Option<Res<TileCollisionMap>>,
+    target_query: Query<WaterReflectionTarget, Without<WaterReflection>>,
+    mut reflection_query: Query<WaterReflectionData>,
+) {
+    for (
+        reflection_entity,
+        reflection,
+        mut reflection_transform,
+        mut reflection_sprite,
+        mut visibility,
+        reflection_atlas,
+    ) in reflection_query.iter_mut()
+    {
+        let Ok((
+            target_transform,
+            target_sprite,
+            target_visibility,
+            kinetics,
+            depth,
+            jump,
+            target_atlas,
+        )) = target_query.get(reflection.target)
+        else {
+            commands.entity(reflection_entity).despawn();
+            continue;
+        };
+
+        let height = jump.map_or(0.0, |jump| jump.height);
+        let submerged = depth.is_some_and(|depth| !depth.at_surface());
+        let over_water = collision_map
+            .as_ref()
+            .is_none_or(|collision_map| collision_map.is_walkable_at(&kinetics.position));
+        if submerged || !over_water || *target_visibility == Visibility::Hidden {
+            *visibility = Visibility::Hidden;
+            continue;
+        }
+        *visibility = Visibility::Inherited;
+
+        // the target sprite gets lifted by its height, the water line sits that far below it
+        let water_y = target_transform.translation.y - height;
+        reflection_transform.translation = Vec3::new(
+            target_transform.translation.x,
+            water_y - REFLECTION_OFFSET - height,
+            REFLECTION_Z_LEVEL,
+        );
+        // a z rotation mirrored across the water line turns the other way
+        reflection_transform.rotation = target_transform.rotation.inverse();
+        // ripples stretching it side to side, a little out of step depending on where it is
+        let wobble = (time.elapsed_seconds() * REFLECTION_WOBBLE_SPEED
+            + target_transform.translation.y * 0.05)
+            .sin()
+            * REFLECTION_WOBBLE;
+        reflection_transform.scale = target_transform.scale
+            * Vec3::new(1.0 + wobble, REFLECTION_SQUASH * (1.0 - wobble), 1.0);
+
+        reflection_sprite.flip_x = target_sprite.flip_x;
+        reflection_sprite.flip_y = !target_sprite.flip_y;
+        // fades as the entity starts to dive and as it gets further from the water
+        let dive_fade = 1.0 - depth.map_or(0.0, |depth| depth.depth / DEPTH_SURFACE_THRESHOLD);
+        let jump_fade = 1.0 - 0.5 * (height / jump_settings.apex_height).min(1.0);
+        reflection_sprite.color = Color::srgba(
+            REFLECTION_TINT.x,
+            REFLECTION_TINT.y,
+            REFLECTION_TINT.z,
+            REFLECTION_ALPHA * dive_fade.clamp(0.0, 1.0) * jump_fade,
+        );
+
+        if let (Some(mut reflection_atlas), Some(target_atlas)) = (reflection_atlas, target_atlas) {
+            reflection_atlas.index = target_atlas.index;
+        }
+    }
+}
@@ -1,12 +1,22 @@
+pub mod ai;
 pub mod anime;
 pub mod audio;
 pub mod bundles;
 pub mod camera;
 pub mod environment;
+pub mod fixed_update_sys;
+pub mod input;
 pub mod kinetic_components;
+pub mod kinetic_res;
+pub mod kinetic_sys;
+pub mod lighting;
 pub mod map;
 pub mod materials;
 pub mod player;
+pub mod replay;
+pub mod rng_res;
+
+use bevy::math::{Vec2, Vec3};
 
 //TODO: move all of these constants to their corresponding resource modules
 
@@ -18,9 +28,14 @@ pub const CAMERA_SCALE_MULTIPLIER: f32 = 1.0;
 
 // TODO: for this whole entire const file figure out when to use proper types (f32 doesnt make sense for tile coordinates etc)
 pub const CAMERA_Z_LEVEL: f32 = 3.0;
-pub const OVERLAY_ANIMATIONS_Z_LEVEL: f32 = 2.0; // TODO: currently overlay Transform inherits from Parent Entity it is attached to, use later
+pub const OVERLAY_ANIMATIONS_Z_LEVEL: f32 = 2.0;
 pub const PLAYER_ENTITY_Z_LEVEL: f32 = 1.0;
 pub const ENVIRONMENT_ENTITY_Z_LEVEL: f32 = 1.0;
+pub const WAKE_TRAIL_Z_LEVEL: f32 = 0.5;
+pub const JUMP_SHADOW_Z_LEVEL: f32 = 0.75;
+pub const REFLECTION_Z_LEVEL: f32 = 0.25;
+pub const RAIN_Z_LEVEL: f32 = 2.5;
+pub const LIGHT_Z_LEVEL: f32 = 1.0;
 
 //-----------------ASSET CONFIGS/SETTINGS-----------------
 
@@ -37,11 +52,30 @@ pub const WAKE_ANIMATION_TEXTURE_ROW_LENGTH: u32 = 1;
 pub const WAKE_ANIMATION_TEXTURE_START_IDX: u32 = 0;
 pub const WAKE_ANIMATION_TEXTURE_END_IDX: u32 = 19;
 pub const WAKE_ANIMATION_SPEED: f32 = 0.05;
+pub const WAKE_TRAIL_EMIT_INTERVAL: f32 = 0.08;
+pub const WAKE_TRAIL_LIFETIME: f32 = 0.6;
+pub const WAKE_TRAIL_MIN_SPEED: f32 = 10.0;
+pub const WAKE_TRAIL_START_SCALE: f32 = 0.6;
+pub const WAKE_TRAIL_END_SCALE: f32 = 1.4;
+pub const OVERLAY_MOVING_MIN_SPEED: f32 = 10.0;
+pub const BUBBLE_ANIMATION_FILE_PATH: &str = "sprite_data/bubbles.png";
+pub const BUBBLE_ANIMATION_TEXTURE_COLUMN_LENGTH: u32 = 8;
+pub const BUBBLE_ANIMATION_TEXTURE_ROW_LENGTH: u32 = 1;
+pub const BUBBLE_ANIMATION_TEXTURE_START_IDX: u32 = 0;
+pub const BUBBLE_ANIMATION_TEXTURE_END_IDX: u32 = 7;
+pub const BUBBLE_ANIMATION_SPEED: f32 = 0.1;
+
+pub const INPUT_CONFIG_FILE_PATH: &str = "config/input_bindings.ron";
+pub const REPLAY_CHECKPOINT_TICKS: u32 = 16;
+pub const STYLUS_TAP_MAX_SECONDS: f32 = 0.25;
+pub const STYLUS_TAP_MAX_DISTANCE: f32 = 8.0;
+pub const STYLUS_ARRIVE_RADIUS: f32 = TILE_SIZE / 4.0;
 
 pub const TILE_MAP_FILE_PATH: &str = "map_data/water.tmx";
 pub const TILE_ANIMATION_TEXTURE_START_IDX: u32 = 40;
 pub const TILE_ANIMATION_TEXTURE_END_IDX: u32 = 54;
 pub const TILE_ANIMATION_SPEED: f32 = 0.1;
+pub const WATER_GLINT_WIDTH: f32 = TILE_SIZE * 0.75;
 
 pub const ENVIRONMENT_ENTITY_ANIMATION_TEXTURE_FILE_PATH: &str = "sprite_data/Ikiikiiruka.png";
 pub const ENVIRONMENT_ENTITY_ANIMATION_TEXTURE_COLUMN_LENGTH: u32 = 8;
@@ -49,11 +83,69 @@ pub const ENVIRONMENT_ENTITY_ANIMATION_TEXTURE_ROW_LENGTH: u32 = 3;
 pub const ENVIRONMENT_ENTITY_ANIMATION_TEXTURE_START_IDX: usize = 0;
 pub const ENVIRONMENT_ENTITY_ANIMATION_TEXTURE_END_IDX: u32 = 18;
 pub const ENVIRONMENT_ENTITY_ANIMATION_SPEED: f32 = 0.2;
+pub const ENVIRONMENT_ENTITY_SPLASH_FRAME: u32 = 7;
 
 //-----------------ENTITY/GAME LOGIC-----------------
 pub const DEFAULT_SPEED: f32 = 150.0;
 pub const PLAYER_ENTITY_SPAWN_X: f32 = 0.0;
 pub const PLAYER_ENTITY_SPAWN_Y: f32 = 0.0;
+pub const COOP_PLAYER_COUNT: usize = 2;
+pub const COOP_PLAYER_TWO_SPAWN_OFFSET_X: f32 = 2.0;
+pub const COOP_MERGE_DISTANCE: f32 = TILE_SIZE * 3.0;
+pub const COOP_SPLIT_DISTANCE: f32 = TILE_SIZE * 4.0;
 
 pub const ENVIRONMENT_ENTITY_SPAWN_X: f32 = 3.0;
 pub const ENVIRONMENT_ENTITY_SPAWN_Y: f32 = 3.0;
+pub const ENVIRONMENT_ENTITY_OBJECT_TYPE: &str = "environment_entity";
+pub const ENVIRONMENT_ENTITY_ORBIT_RADIUS: f32 = 2.0;
+pub const SCHOOL_SPAWNER_OBJECT_TYPE: &str = "school_spawner";
+pub const SCHOOL_DEFAULT_COUNT: usize = 12;
+pub const SCHOOL_DEFAULT_RADIUS: f32 = 1.0;
+pub const ENVIRONMENT_SPAWNER_OBJECT_TYPE: &str = "environment_spawner";
+pub const ENVIRONMENT_SPAWNER_DEFAULT_RATE: f32 = 0.5;
+pub const ENVIRONMENT_SPAWNER_DEFAULT_MAX_POPULATION: usize = 8;
+pub const ENVIRONMENT_SPAWN_ATTEMPTS: usize = 8;
+pub const ENVIRONMENT_OFFSCREEN_DESPAWN_SECONDS: f32 = 5.0;
+
+pub const DAY_LENGTH_SECONDS: f32 = 240.0;
+pub const START_HOUR: f32 = 20.0;
+pub const MOONRISE_HOUR: f32 = 18.0;
+pub const LUNAR_CYCLE_GAME_DAYS: f32 = 8.0;
+pub const REAL_LUNAR_CYCLE_DAYS: f64 = 29.530_588;
+// 2000-01-06 18:14 UTC
+pub const REAL_NEW_MOON_UNIX_SECONDS: f64 = 947_182_440.0;
+pub const MOONLIGHT_NEW_MOON_INTENSITY: f32 = 0.15;
+pub const MOON_EARTHSHINE: f32 = 0.12;
+pub const MOON_SIZE: f32 = 128.0;
+// where the disc sits in full_moon.png, in uv units
+pub const MOON_DISC_CENTER: Vec2 = Vec2::new(0.5, 0.5);
+pub const MOON_DISC_RADIUS: f32 = 120.0 / 256.0;
+
+pub const MAX_LIGHTS: usize = 64;
+pub const MOONLIGHT_RADIUS: f32 = TILE_SIZE * 64.0;
+pub const MOONLIGHT_STRENGTH: f32 = 0.35;
+pub const LIGHT_OBJECT_TYPE: &str = "light";
+pub const LIGHT_DEFAULT_INTENSITY: f32 = 1.0;
+pub const LIGHT_DEFAULT_RADIUS: f32 = 3.0;
+pub const GLOW_DEFAULT_RADIUS: f32 = 1.0;
+
+pub const WEATHER_TRANSITION_SECONDS: f32 = 20.0;
+pub const WEATHER_CHANGE_SECONDS: f32 = 90.0;
+pub const WEATHER_FOG_DRIFT_SPEED: f32 = 40.0;
+pub const WEATHER_WATER_ANIMATION_WIND_SCALE: f32 = 1.5;
+pub const RAIN_DROP_FALL_SPEED: f32 = 600.0;
+pub const RAIN_DROP_WIND_PUSH: f32 = 250.0;
+pub const RAIN_DROP_LENGTH: f32 = 14.0;
+
+pub const DIVE_SPEED: f32 = 2.5; // depth units per second, surface to underwater is 1.0
+pub const DEPTH_SURFACE_THRESHOLD: f32 = 0.5;
+pub const UNDERWATER_SCALE: f32 = 0.8;
+pub const UNDERWATER_TINT: Vec3 = Vec3::new(0.35, 0.55, 0.8);
+pub const JUMP_SHADOW_ALPHA: f32 = 0.35;
+pub const REFLECTION_ALPHA: f32 = 0.4;
+pub const REFLECTION_TINT: Vec3 = Vec3::new(0.6, 0.7, 0.9);
+// how far below the entity the mirrored copy starts while it's swimming
+pub const REFLECTION_OFFSET: f32 = TILE_SIZE * 0.25;
+pub const REFLECTION_SQUASH: f32 = 0.8;
+pub const REFLECTION_WOBBLE: f32 = 0.06;
+pub const REFLECTION_WOBBLE_SPEED: f32 = 4.0;
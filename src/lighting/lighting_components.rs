@@ -0,0 +1,12 @@
+use bevy::{math::Vec3, prelude::Component};
+
+// a point light, picked up by collect_lights as long as the entity is visible. only the tilemap
+// materials and ReflectionMaterial shade with it, plain sprites (players, critters) stay unlit so
+// a glowing critter shows up through the light it throws on the water around it
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Light2d {
+    pub color: Vec3,
+    pub intensity: f32,
+    // world units, nothing past this gets any light
+    pub radius: f32,
+}
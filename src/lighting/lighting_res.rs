@@ -0,0 +1,53 @@
+use bevy::{
+    math::{Vec2, Vec3},
+    prelude::Resource,
+    render::render_resource::ShaderType,
+};
+
+use crate::{MAX_LIGHTS, MOONLIGHT_RADIUS, MOONLIGHT_STRENGTH};
+
+// one light the way the shaders see it, keep in sync with Light2d in fog.wgsl and reflections.wgsl
+#[derive(ShaderType, Clone, Copy, Debug, Default, PartialEq)]
+pub struct GpuLight2d {
+    pub position: Vec2,
+    pub radius: f32,
+    pub intensity: f32,
+    pub color: Vec3,
+}
+
+// every light in the scene this frame, rebuilt by collect_lights
+#[derive(Resource, Debug, Default)]
+pub struct SceneLights {
+    pub lights: Vec<GpuLight2d>,
+}
+
+impl SceneLights {
+    // storage buffers can't be empty, a dark light stands in when there's nothing to shine
+    pub fn gpu_lights(&self) -> Vec<GpuLight2d> {
+        if self.lights.is_empty() {
+            vec![GpuLight2d::default()]
+        } else {
+            self.lights.clone()
+        }
+    }
+}
+
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct LightingSettings {
+    // anything past this many lights is dropped, the moon always makes it in
+    pub max_lights: usize,
+    // the moon is far away, so it gets a radius that covers the whole map
+    pub moonlight_radius: f32,
+    // the day/night tint already darkens the map, the moon only adds a little on top
+    pub moonlight_strength: f32,
+}
+
+impl Default for LightingSettings {
+    fn default() -> Self {
+        Self {
+            max_lights: MAX_LIGHTS,
+            moonlight_radius: MOONLIGHT_RADIUS,
+            moonlight_strength: MOONLIGHT_STRENGTH,
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use bevy::{
+    core::Name,
+    math::Vec3,
+    prelude::{
+        Commands, DetectChanges, GlobalTransform, InheritedVisibility, Query, Res, ResMut,
+        SpatialBundle, Transform,
+    },
+};
+use bevy_asset::Assets;
+use tiled::LayerType;
+
+use crate::{
+    environment::moon::MoonLightSource,
+    lighting::{
+        lighting_components::Light2d,
+        lighting_res::{GpuLight2d, LightingSettings, SceneLights},
+    },
+    map::{
+        tiled_res::{TiledMapAssets, TiledMapSource},
+        tiled_sys::{color_property, float_property, tiled_object_world_position},
+    },
+    materials::{fog::FogMaterial, reflections::ReflectionMaterial, water::WaterMaterial},
+    LIGHT_DEFAULT_INTENSITY, LIGHT_DEFAULT_RADIUS, LIGHT_OBJECT_TYPE, LIGHT_Z_LEVEL, TILE_SIZE,
+};
+
+// one light per `light` object in the map: `color`, `intensity` and `radius` in tiles
+pub fn spawn_tiled_lights(
+    mut commands: Commands,
+    tiled_asset: Res<TiledMapAssets>,
+    map_assets: Res<Assets<TiledMapSource>>,
+) {
+    let Some(tiled_map) = map_assets.get(&tiled_asset.tiled_map) else {
+        return;
+    };
+    for layer in tiled_map.rs_tiled_map.layers() {
+        let LayerType::Objects(object_layer) = layer.layer_type() else {
+            continue;
+        };
+        for object in object_layer.objects() {
+            if object.user_type != LIGHT_OBJECT_TYPE {
+                continue;
+            }
+            let position = tiled_object_world_position(&object);
+            commands
+                .spawn(SpatialBundle::from_transform(Transform::from_xyz(
+                    position.x,
+                    position.y,
+                    LIGHT_Z_LEVEL,
+                )))
+                .insert(Light2d {
+                    color: color_property(&object.properties, "color").unwrap_or(Vec3::ONE),
+                    intensity: float_property(&object.properties, "intensity")
+                        .unwrap_or(LIGHT_DEFAULT_INTENSITY),
+                    radius: float_property(&object.properties, "radius")
+                        .unwrap_or(LIGHT_DEFAULT_RADIUS)
+                        * TILE_SIZE,
+                })
+                .insert(Name::new(format!("Light {}", object.name)));
+        }
+    }
+}
+
+// has to run after move_moon_with_time_of_day and apply_lunar_phase_to_moon so the moon is current
+pub fn collect_lights(
+    settings: Res<LightingSettings>,
+    mut scene_lights: ResMut<SceneLights>,
+    moon_query: Query<&MoonLightSource>,
+    light_query: Query<(&GlobalTransform, &Light2d, &InheritedVisibility)>,
+) {
+    let moon_lights = moon_query.iter().map(|moonlight| GpuLight2d {
+        position: moonlight.position,
+        radius: settings.moonlight_radius,
+        intensity: moonlight.intensity * settings.moonlight_strength,
+        color: moonlight.color,
+    });
+    let lights = light_query
+        .iter()
+        .filter(|(_, light, visibility)| visibility.get() && light.intensity > 0.0)
+        .map(|(transform, light, _)| GpuLight2d {
+            position: transform.translation().truncate(),
+            radius: light.radius,
+            intensity: light.intensity,
+            color: light.color,
+        });
+    let collected: Vec<GpuLight2d> = moon_lights
+        .chain(lights)
+        .take(settings.max_lights)
+        .collect();
+
+    // only touch the resource when something moved, so the materials don't get rewritten for nothing
+    if collected != scene_lights.lights {
+        scene_lights.lights = collected;
+    }
+}
+
+// every material kind is optional, the ds_reflection example only registers ReflectionMaterial
+pub fn apply_lights_to_materials(
+    scene_lights: Res<SceneLights>,
+    fog_materials: Option<ResMut<Assets<FogMaterial>>>,
+    water_materials: Option<ResMut<Assets<WaterMaterial>>>,
+    reflection_materials: Option<ResMut<Assets<ReflectionMaterial>>>,
+) {
+    if !scene_lights.is_changed() {
+        return;
+    }
+    let lights = scene_lights.gpu_lights();
+    if let Some(mut fog_materials) = fog_materials {
+        for (_, material) in fog_materials.iter_mut() {
+            material.lights.clone_from(&lights);
+        }
+    }
+    if let Some(mut water_materials) = water_materials {
+        for (_, material) in water_materials.iter_mut() {
+            material.lights.clone_from(&lights);
+        }
+    }
+    if let Some(mut reflection_materials) = reflection_materials {
+        for (_, material) in reflection_materials.iter_mut() {
+            material.lights.clone_from(&lights);
+        }
+    }
+}
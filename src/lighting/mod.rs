@@ -0,0 +1,3 @@
+pub mod lighting_components;
+pub mod lighting_res;
+pub mod lighting_sys;
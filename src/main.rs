@@ -1,11 +1,13 @@
 use bevy::{
-    app::{FixedUpdate, Update},
+    app::{FixedUpdate, Last, PreUpdate, Startup, Update},
     asset::AssetApp,
+    input::InputSystem,
     log::info,
     prelude::{
-        in_state, App, AppExtStates, DefaultPlugins, ImagePlugin, IntoSystemConfigs, NextState,
-        OnEnter, PluginGroup, ResMut, States, Window, WindowPlugin,
+        in_state, resource_exists, App, AppExtStates, DefaultPlugins, ImagePlugin,
+        IntoSystemConfigs, NextState, OnEnter, PluginGroup, ResMut, States, Window, WindowPlugin,
     },
+    sprite::Material2dPlugin,
     window::WindowResolution,
 };
 use bevy_asset_loader::{
@@ -15,30 +17,79 @@ use bevy_asset_loader::{
 use bevy_ecs_tilemap::{prelude::MaterialTilemapPlugin, TilemapPlugin};
 use tracy_client::Client;
 use yakuzaishi::{
+    ai::{
+        ai_res::{FlockingSettings, SpatialGrid, SteeringBehaviorSettings},
+        path_res::{PathfindingGrid, PathfindingQueue, PathfindingSettings},
+    },
     anime::{
         anime_res::{
             EnvironmentEntityAnimationAssets, OverlayAnimationAssets, PlayerEntityAnimationAssets,
+            RippleSettings,
         },
         environment_anime_sys::animate_env_entity_animations,
+        frame_event_sys::AnimationFrameEvent,
         map_anime_sys::{
-            animate_overlapped_tiles_event_based, handle_overlap_event, TileAnimationEvent,
+            animate_tile_ripples, handle_overlap_event, spread_tile_ripples,
+            start_pending_tile_ripples,
         },
         overlay_anime_sys::{
             animate_overlay_animations, attach_overlay_animation_to_player_entity,
+            spawn_overlay_attachments, sync_attached_overlays,
         },
+        wake_sys::{emit_wake_trails, fade_wake_trails, setup_wake_trail_pool},
     },
     audio::audio_res::AudioAssets,
-    camera::camera_2d_sys::{bottom_camera, top_camera, track_camera},
+    camera::camera_2d_sys::{
+        assign_camera_targets, bottom_camera, resize_coop_viewports, top_camera, track_camera,
+    },
     environment::{
+        day_night::{apply_time_of_day_to_fog, move_moon_with_time_of_day, TimeOfDay},
         environment_sys::spawn_environment_entity,
         moon::{place_moon, MoonAsset},
+        moon_phase::{
+            apply_lunar_phase_to_moon, setup_lunar_phase_from_args, LunarPhase, MoonPhaseChanged,
+        },
+        weather::{
+            apply_weather_to_fog, fall_rain_drops, load_weather_from_map, spawn_rain_drops, Weather,
+        },
+    },
+    fixed_update_sys::fixed_update_systems,
+    input::{
+        input_res::PointerState,
+        input_sys::{
+            gather_input_actions, load_input_config, steer_towards_pointer,
+            track_bottom_screen_pointer, PokeEvent,
+        },
+    },
+    kinetic_res::{JumpSettings, SwimPhysicsSettings},
+    kinetic_sys::{
+        apply_depth_and_jump_appearance, interpolate_kinetic_transforms, spawn_jump_shadows,
+        spawn_water_reflections, sync_jump_shadows, sync_water_reflections, SplashEvent,
+    },
+    lighting::{
+        lighting_res::{LightingSettings, SceneLights},
+        lighting_sys::{apply_lights_to_materials, collect_lights, spawn_tiled_lights},
     },
     map::{
+        tile_overlap_sys::{
+            attach_tile_occupancy, emit_tile_overlap_events, TileEntered, TileExited, TileStay,
+        },
         tiled_res::{TiledLoader, TiledMapAssets, TiledMapSource},
-        tiled_sys::{spawn_tiled_map, update_time_on_shader},
+        tiled_sys::{
+            spawn_tiled_map, update_depth_layer_visibility, update_time_on_shader,
+            update_water_glint,
+        },
+    },
+    materials::{fog::FogMaterial, moon_phase::MoonPhaseMaterial, water::WaterMaterial},
+    player::{
+        player_res::{CoopSettings, CoopView},
+        player_sys::{setup_coop_from_args, spawn_player_entity},
     },
-    materials::fog::FogMaterial,
-    player::player_sys::{control_player_entity, spawn_player_entity},
+    replay::{
+        replay_res::ReplayRecorder,
+        replay_sys::{save_replay_on_exit, setup_replay_from_args},
+    },
+    rng_res::EffectsRng,
     NINTENDO_DS_SCREEN_HEIGHT, NINTENDO_DS_SCREEN_WIDTH,
 };
 
@@ -65,10 +116,55 @@ fn main() {
             // WorldInspectorPlugin::new(),
             TilemapPlugin,
             MaterialTilemapPlugin::<FogMaterial>::default(),
+            MaterialTilemapPlugin::<WaterMaterial>::default(),
+            Material2dPlugin::<MoonPhaseMaterial>::default(),
         ))
         .init_asset::<TiledMapSource>()
         .register_asset_loader(TiledLoader)
-        .add_event::<TileAnimationEvent>()
+        .init_resource::<RippleSettings>()
+        .init_resource::<SwimPhysicsSettings>()
+        .init_resource::<JumpSettings>()
+        .init_resource::<SteeringBehaviorSettings>()
+        .init_resource::<FlockingSettings>()
+        .init_resource::<SpatialGrid>()
+        .init_resource::<PathfindingGrid>()
+        .init_resource::<PathfindingQueue>()
+        .init_resource::<PathfindingSettings>()
+        .init_resource::<TimeOfDay>()
+        .init_resource::<Weather>()
+        .init_resource::<EffectsRng>()
+        .init_resource::<LunarPhase>()
+        .init_resource::<SceneLights>()
+        .init_resource::<LightingSettings>()
+        .add_event::<MoonPhaseChanged>()
+        .add_event::<SplashEvent>()
+        .init_resource::<CoopSettings>()
+        .init_resource::<CoopView>()
+        .add_systems(
+            Startup,
+            (
+                load_input_config,
+                setup_replay_from_args,
+                setup_coop_from_args,
+                setup_lunar_phase_from_args,
+            ),
+        )
+        .init_resource::<PointerState>()
+        .add_event::<PokeEvent>()
+        .add_systems(
+            PreUpdate,
+            (
+                gather_input_actions,
+                track_bottom_screen_pointer,
+                steer_towards_pointer,
+            )
+                .chain()
+                .after(InputSystem),
+        )
+        .add_event::<AnimationFrameEvent>()
+        .add_event::<TileEntered>()
+        .add_event::<TileExited>()
+        .add_event::<TileStay>()
         .init_state::<GameState>()
         .add_loading_state(
             LoadingState::new(GameState::AssetLoading)
@@ -87,8 +183,10 @@ fn main() {
                 // TODO: this does not work at all yet, still learning how 3D meshes and materials works
                 //spawn_tiled_map_3d,
                 spawn_tiled_map,
+                load_weather_from_map,
                 spawn_player_entity,
                 spawn_environment_entity,
+                spawn_tiled_lights,
                 place_moon,
                 // TODO: whatever just gross, figure out how to make this transition more intuitive
                 transition_to_run_state,
@@ -102,24 +200,80 @@ fn main() {
                 //  so unlike top_camera it can be placed in AssetProcessing fine (top_camera cant)
                 //  ^^THIS IS BAD DESIGN BY THE FUCKING WAY
                 bottom_camera,
+                setup_wake_trail_pool,
                 attach_overlay_animation_to_player_entity,
             ),
         )
         .add_systems(
             FixedUpdate,
-            control_player_entity.run_if(in_state(GameState::Run)),
+            fixed_update_systems().run_if(in_state(GameState::Run)),
+        )
+        .add_systems(
+            Last,
+            save_replay_on_exit.run_if(resource_exists::<ReplayRecorder>),
         )
         .add_systems(
             Update,
             (
-                track_camera.run_if(in_state(GameState::Run)),
-                animate_overlapped_tiles_event_based.run_if(in_state(GameState::Run)),
-                handle_overlap_event.run_if(in_state(GameState::Run)),
+                interpolate_kinetic_transforms.run_if(in_state(GameState::Run)),
+                (assign_camera_targets, resize_coop_viewports, track_camera)
+                    .chain()
+                    .after(interpolate_kinetic_transforms)
+                    .run_if(in_state(GameState::Run)),
+                (
+                    attach_tile_occupancy,
+                    emit_tile_overlap_events,
+                    handle_overlap_event,
+                    start_pending_tile_ripples,
+                    spread_tile_ripples,
+                    animate_tile_ripples,
+                )
+                    .chain()
+                    .after(interpolate_kinetic_transforms)
+                    .run_if(in_state(GameState::Run)),
                 // TODO: sometimes when I have the overlay animations on after like several
                 //  environment entity animation loop cycles the sprite breaks
-                animate_overlay_animations.run_if(in_state(GameState::Run)),
+                (
+                    spawn_overlay_attachments,
+                    sync_attached_overlays,
+                    animate_overlay_animations,
+                )
+                    .chain()
+                    .after(interpolate_kinetic_transforms)
+                    .run_if(in_state(GameState::Run)),
                 animate_env_entity_animations.run_if(in_state(GameState::Run)),
+                (emit_wake_trails, fade_wake_trails)
+                    .chain()
+                    .after(interpolate_kinetic_transforms)
+                    .run_if(in_state(GameState::Run)),
                 update_time_on_shader.run_if(in_state(GameState::Run)),
+                (
+                    move_moon_with_time_of_day,
+                    apply_lunar_phase_to_moon,
+                    apply_time_of_day_to_fog,
+                    apply_weather_to_fog,
+                    collect_lights,
+                    apply_lights_to_materials,
+                    update_water_glint,
+                )
+                    .chain()
+                    .after(track_camera)
+                    .run_if(in_state(GameState::Run)),
+                (spawn_rain_drops, fall_rain_drops)
+                    .chain()
+                    .after(track_camera)
+                    .run_if(in_state(GameState::Run)),
+                (
+                    apply_depth_and_jump_appearance,
+                    spawn_jump_shadows,
+                    sync_jump_shadows,
+                    spawn_water_reflections,
+                    sync_water_reflections,
+                )
+                    .chain()
+                    .after(interpolate_kinetic_transforms)
+                    .run_if(in_state(GameState::Run)),
+                update_depth_layer_visibility.run_if(in_state(GameState::Run)),
             ),
         )
         .run();
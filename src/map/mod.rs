@@ -1,3 +1,4 @@
+pub mod tile_overlap_sys;
 pub mod tiled_3d_sys;
 pub mod tiled_components;
 pub mod tiled_res;
@@ -0,0 +1,135 @@
+use bevy::{
+    math::{Vec2, Vec3},
+    prelude::{Commands, Entity, Event, EventWriter, Query, With, Without},
+};
+use bevy_ecs_tilemap::prelude::{TilePos, TileStorage};
+use tracy_client::span;
+
+use crate::{
+    kinetic_components::{JumpComponent, KineticEntityComponents},
+    map::tiled_components::{OccupiedTile, TileOccupancy},
+    TILE_SIZE,
+};
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct TileEntered {
+    pub entity: Entity,
+    pub tilemap: Entity,
+    pub tile: Entity,
+    pub tile_pos: TilePos,
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct TileExited {
+    pub entity: Entity,
+    pub tilemap: Entity,
+    pub tile: Entity,
+    pub tile_pos: TilePos,
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct TileStay {
+    pub entity: Entity,
+    pub tilemap: Entity,
+    pub tile: Entity,
+    pub tile_pos: TilePos,
+}
+
+pub fn attach_tile_occupancy(
+    mut commands: Commands,
+    query: Query<Entity, (With<KineticEntityComponents>, Without<TileOccupancy>)>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).insert(TileOccupancy::default());
+    }
+}
+
+pub fn emit_tile_overlap_events(
+    mut kinetic_query: Query<(
+        Entity,
+        &KineticEntityComponents,
+        &mut TileOccupancy,
+        Option<&JumpComponent>,
+    )>,
+    tilemap_query: Query<(Entity, &TileStorage)>,
+    mut entered_writer: EventWriter<TileEntered>,
+    mut exited_writer: EventWriter<TileExited>,
+    mut stay_writer: EventWriter<TileStay>,
+) {
+    let _span = span!("tile overlap events");
+    for (entity, kinetics, mut occupancy, jump) in kinetic_query.iter_mut() {
+        let mut current_tiles = Vec::with_capacity(occupancy.tiles.len());
+        // while airborne nothing is touched, leaving the water reads as an exit and landing as an enter
+        let airborne = jump.is_some_and(JumpComponent::is_airborne);
+        if let Some(tile_pos) = calc_checked_tile_pos(&kinetics.position).filter(|_| !airborne) {
+            for (tilemap, tile_storage) in tilemap_query.iter() {
+                if let Some(tile) = tile_storage.checked_get(&tile_pos) {
+                    current_tiles.push(OccupiedTile {
+                        tilemap,
+                        tile,
+                        tile_pos,
+                    });
+                }
+            }
+        }
+
+        for occupied in current_tiles.iter() {
+            if occupancy.tiles.contains(occupied) {
+                stay_writer.send(TileStay {
+                    entity,
+                    tilemap: occupied.tilemap,
+                    tile: occupied.tile,
+                    tile_pos: occupied.tile_pos,
+                });
+            } else {
+                entered_writer.send(TileEntered {
+                    entity,
+                    tilemap: occupied.tilemap,
+                    tile: occupied.tile,
+                    tile_pos: occupied.tile_pos,
+                });
+            }
+        }
+
+        for previous in occupancy.tiles.iter() {
+            if !current_tiles.contains(previous) {
+                exited_writer.send(TileExited {
+                    entity,
+                    tilemap: previous.tilemap,
+                    tile: previous.tile,
+                    tile_pos: previous.tile_pos,
+                });
+            }
+        }
+
+        occupancy.tiles = current_tiles;
+    }
+}
+
+pub fn calc_tile_pos(translation: &Vec3) -> TilePos {
+    let tile_x = ((translation.x + (TILE_SIZE / 2.0)) / TILE_SIZE).floor();
+    let tile_y = ((translation.y + (TILE_SIZE / 2.0)) / TILE_SIZE).floor();
+    TilePos {
+        x: tile_x as u32,
+        y: tile_y as u32,
+    }
+}
+
+// the world position of a tile's center, the inverse of calc_tile_pos
+pub fn tile_world_position(tile_pos: &TilePos) -> Vec2 {
+    Vec2::new(tile_pos.x as f32 * TILE_SIZE, tile_pos.y as f32 * TILE_SIZE)
+}
+
+// same as calc_tile_pos but anything left of or below the map is None instead of
+// getting saturated onto row/column 0
+pub fn calc_checked_tile_pos(translation: &Vec3) -> Option<TilePos> {
+    let tile_x = ((translation.x + (TILE_SIZE / 2.0)) / TILE_SIZE).floor();
+    let tile_y = ((translation.y + (TILE_SIZE / 2.0)) / TILE_SIZE).floor();
+    if tile_x < 0.0 || tile_y < 0.0 {
+        return None;
+    }
+    Some(TilePos {
+        x: tile_x as u32,
+        y: tile_y as u32,
+    })
+}
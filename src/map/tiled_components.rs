@@ -1,4 +1,42 @@
-use bevy::prelude::Component;
+use bevy::prelude::{Component, Entity};
+use bevy_ecs_tilemap::tiles::TilePos;
+
+use crate::kinetic_components::DepthLevel;
 
 #[derive(Component, Default)]
 pub struct TileEntityTag;
+
+// every surface tile that isn't `solid` is open water, the only tiles anything can swim through
+#[derive(Component, Default)]
+pub struct WaterTileTag;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OccupiedTile {
+    pub tilemap: Entity,
+    pub tile: Entity,
+    pub tile_pos: TilePos,
+}
+
+// every tile (one per tilemap layer) that a kinetic entity currently overlaps
+#[derive(Component, Default, Debug)]
+pub struct TileOccupancy {
+    pub tiles: Vec<OccupiedTile>,
+}
+
+// set from the "depth" string property ("surface" or "underwater") of a tiled layer,
+// layers without the property are visible at every depth
+#[derive(Component, Clone, Copy, Debug)]
+pub struct TilemapDepthLayer(pub DepthLevel);
+
+// only on surface layer tiles, change it to reroute pathfinding and collision around the tile.
+// cost is how expensive the tile is to swim through, None blocks it
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct TileNavigation {
+    pub cost: Option<f32>,
+}
+
+impl Default for TileNavigation {
+    fn default() -> Self {
+        Self { cost: Some(1.0) }
+    }
+}
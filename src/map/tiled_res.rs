@@ -9,17 +9,21 @@ use std::{
 
 use bevy::{
     asset::{io::Reader, Asset, AssetLoader, AssetPath, LoadContext},
+    math::Vec3,
     prelude::{Resource, TypePath},
     utils::ConditionalSendFuture,
 };
 use bevy_asset::Handle;
 use bevy_asset_loader::asset_collection::AssetCollection;
+use bevy_ecs_tilemap::{map::TilemapSize, tiles::TilePos};
 use bevy_render::texture::Image;
 // TODO: How do these next two "uses" even work?
 use futures_lite::AsyncReadExt;
 use thiserror::Error;
 use tiled::{DefaultResourceCache, Loader, ResourceReader};
 
+use crate::map::tile_overlap_sys::calc_checked_tile_pos;
+
 #[derive(AssetCollection, Resource)]
 pub struct TiledMapAssets {
     #[asset(path = "map_data/water.tmx")]
@@ -32,6 +36,37 @@ pub struct TiledMapSource {
     pub bevy_ecs_tilemap_textures: bevy_ecs_tilemap::map::TilemapTexture,
 }
 
+// which tiles kinetic entities can swim through, tiles with a `solid = true` tile property and
+// anything outside of the map are blocked. built from the surface tile layers when the map spawns
+#[derive(Resource, Debug)]
+pub struct TileCollisionMap {
+    pub size: TilemapSize,
+    solid: Vec<bool>,
+}
+
+impl TileCollisionMap {
+    pub fn new(size: TilemapSize) -> Self {
+        Self {
+            size,
+            solid: vec![false; size.count()],
+        }
+    }
+
+    pub fn set_solid(&mut self, tile_pos: &TilePos, solid: bool) {
+        if tile_pos.within_map_bounds(&self.size) {
+            self.solid[tile_pos.to_index(&self.size)] = solid;
+        }
+    }
+
+    pub fn is_walkable(&self, tile_pos: &TilePos) -> bool {
+        tile_pos.within_map_bounds(&self.size) && !self.solid[tile_pos.to_index(&self.size)]
+    }
+
+    pub fn is_walkable_at(&self, translation: &Vec3) -> bool {
+        calc_checked_tile_pos(translation).is_some_and(|tile_pos| self.is_walkable(&tile_pos))
+    }
+}
+
 pub struct TiledLoader;
 
 impl AssetLoader for TiledLoader {
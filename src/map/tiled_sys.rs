@@ -1,60 +1,106 @@
 use bevy::{
     core::Name,
     log::info,
-    math::Vec3,
-    prelude::{Commands, Entity, Res, ResMut, Transform},
+    math::{Vec2, Vec3},
+    prelude::{
+        Commands, Entity, GlobalTransform, OrthographicProjection, Query, Res, ResMut, Transform,
+        Visibility, With,
+    },
     time::{Time, Timer, TimerMode},
 };
-use bevy_asset::{Assets, Handle};
+use bevy_asset::{AssetServer, Assets, Handle};
 use bevy_ecs_tilemap::{
-    map::{TilemapGridSize, TilemapId, TilemapSize, TilemapSpacing, TilemapTileSize},
-    prelude::{TilePos, TileStorage},
+    map::{
+        TilemapGridSize, TilemapId, TilemapSize, TilemapSpacing, TilemapTexture, TilemapTileSize,
+    },
+    prelude::{MaterialTilemap, TilePos, TileStorage},
     tiles::{TileBundle, TileFlip, TileTextureIndex},
     MaterialTilemapBundle,
 };
-use tiled::{LayerType, TileLayer};
+use tiled::{Layer, LayerTile, LayerType, Map, ObjectData, Properties, PropertyValue, TileLayer};
 
 use crate::{
+    ai::path_res::PathfindingGrid,
     anime::anime_components::{AnimationComponent, AnimationTimer},
+    camera::{
+        camera_2d_sys::camera_view,
+        camera_components::{BottomCameraTag, TopCameraTag},
+    },
+    environment::moon::{MoonLightSource, MoonTag},
+    kinetic_components::{DepthComponent, DepthLevel, PlayerEntityTag},
+    lighting::lighting_res::SceneLights,
     map::{
-        tiled_components::TileEntityTag,
-        tiled_res::{TiledMapAssets, TiledMapSource},
+        tiled_components::{TileEntityTag, TileNavigation, TilemapDepthLayer, WaterTileTag},
+        tiled_res::{TileCollisionMap, TiledMapAssets, TiledMapSource},
+    },
+    materials::{
+        fog::{FogMaterial, FogUniform},
+        water::{tilemap_fogs, WaterMaterial},
     },
-    materials::fog::FogMaterial,
     TILE_ANIMATION_SPEED, TILE_ANIMATION_TEXTURE_END_IDX, TILE_ANIMATION_TEXTURE_START_IDX,
-    TILE_SIZE,
+    TILE_SIZE, WATER_GLINT_WIDTH,
 };
 
 pub fn spawn_tiled_map(
     mut commands: Commands,
+    asset_server: Res<AssetServer>,
     map_assets: Res<Assets<TiledMapSource>>,
     tiled_asset: Res<TiledMapAssets>,
-    mut materials: ResMut<Assets<FogMaterial>>,
+    mut fog_materials: ResMut<Assets<FogMaterial>>,
+    mut water_materials: ResMut<Assets<WaterMaterial>>,
 ) {
     info!("process_tiled_maps: Starting");
     let map_handle: Handle<TiledMapSource> = tiled_asset.tiled_map.clone();
 
     if let Some(tiled_map) = map_assets.get(&map_handle) {
-        process_tileset(&mut commands, tiled_map, &mut materials);
+        process_tileset(
+            &mut commands,
+            &asset_server,
+            tiled_map,
+            &mut fog_materials,
+            &mut water_materials,
+        );
     }
     info!("process_tiled_maps: ENDING");
 }
 
 fn process_tileset(
     commands: &mut Commands,
+    asset_server: &AssetServer,
     tiled_map: &TiledMapSource,
-    materials: &mut Assets<FogMaterial>,
+    fog_materials: &mut Assets<FogMaterial>,
+    water_materials: &mut Assets<WaterMaterial>,
 ) {
     // TODO: THIS IS SPECIAL 0!!!! (ONLY 1 tileset per map assumed)
     let tileset = &tiled_map.rs_tiled_map.tilesets()[0];
     let tilemap_texture = &tiled_map.bevy_ecs_tilemap_textures;
 
-    let fog_material_handle = materials.add(FogMaterial {
-        time: 0.0,
-        density: 0.5,
-        fog_color: Vec3::new(1.0, 1.0, 1.0),
-        wind_dir: Vec3::new(1.0, 0.0, 0.0),
-        _padding: Vec3::ZERO,
+    // `normal_map` is an asset path to a normal map laid out exactly like the tileset image
+    let map_properties = &tiled_map.rs_tiled_map.properties;
+    let normal_map = match map_properties.get("normal_map") {
+        Some(PropertyValue::StringValue(path)) => Some(asset_server.load(path.clone())),
+        _ => None,
+    };
+    let normal_map_strength = if normal_map.is_some() {
+        float_property(map_properties, "normal_map_strength").unwrap_or(1.0)
+    } else {
+        0.0
+    };
+
+    // the sea floor only gets the fog, the moon glints off of the surface layers
+    let fog_material_handle = fog_materials.add(FogMaterial {
+        fog: FogUniform::default(),
+        normal_map_strength,
+        lights: SceneLights::default().gpu_lights(),
+        normal_map: normal_map.clone(),
+    });
+    let water_material_handle = water_materials.add(WaterMaterial {
+        fog: FogUniform::default(),
+        normal_map_strength,
+        glint_width: WATER_GLINT_WIDTH,
+        lights: SceneLights::default().gpu_lights(),
+        normal_map,
+        ..Default::default()
     });
 
     let tile_spacing = TilemapSpacing {
@@ -73,23 +119,49 @@ fn process_tileset(
                 x: tiled_map.rs_tiled_map.tile_width as f32,
                 y: tiled_map.rs_tiled_map.tile_height as f32,
             };
-            let tile_storage =
-                process_tile_layer(commands, tile_layer, map_size, TilemapId(layer_entity));
-
-            commands
-                .entity(layer_entity)
-                .insert(MaterialTilemapBundle {
-                    grid_size,
-                    size: map_size,
-                    storage: tile_storage,
-                    texture: tilemap_texture.clone(),
-                    tile_size: TilemapTileSize::new(TILE_SIZE, TILE_SIZE),
-                    transform: Transform::from_xyz(0.0, 0.0, 0.0),
-                    spacing: tile_spacing,
-                    material: fog_material_handle.clone(),
-                    ..Default::default()
-                })
-                .insert(Name::new("TiledMap With Fog Entity"));
+            let depth_level = layer_depth_level(&layer);
+            let tile_storage = process_tile_layer(
+                commands,
+                tile_layer,
+                map_size,
+                TilemapId(layer_entity),
+                blocks_movement(depth_level),
+            );
+
+            match depth_level {
+                None | Some(DepthLevel::Surface) => {
+                    commands
+                        .entity(layer_entity)
+                        .insert(material_tilemap_bundle(
+                            grid_size,
+                            map_size,
+                            tile_storage,
+                            tilemap_texture.clone(),
+                            tile_spacing,
+                            water_material_handle.clone(),
+                        ))
+                        .insert(Name::new("TiledMap With Water Entity"));
+                }
+                Some(DepthLevel::Underwater) => {
+                    commands
+                        .entity(layer_entity)
+                        .insert(material_tilemap_bundle(
+                            grid_size,
+                            map_size,
+                            tile_storage,
+                            tilemap_texture.clone(),
+                            tile_spacing,
+                            fog_material_handle.clone(),
+                        ))
+                        .insert(Name::new("TiledMap With Fog Entity"));
+                }
+            }
+
+            if let Some(depth_level) = depth_level {
+                commands
+                    .entity(layer_entity)
+                    .insert(TilemapDepthLayer(depth_level));
+            }
         } else {
             info!(
                 "Skipping layer {} because only tile layers are supported.",
@@ -97,6 +169,103 @@ fn process_tileset(
             );
         }
     }
+
+    commands.insert_resource(build_tile_collision_map(&tiled_map.rs_tiled_map));
+}
+
+// every layer is laid out the same, only the material differs between the surface and the sea floor
+fn material_tilemap_bundle<M: MaterialTilemap + Default>(
+    grid_size: TilemapGridSize,
+    map_size: TilemapSize,
+    tile_storage: TileStorage,
+    texture: TilemapTexture,
+    spacing: TilemapSpacing,
+    material: Handle<M>,
+) -> MaterialTilemapBundle<M> {
+    MaterialTilemapBundle {
+        grid_size,
+        size: map_size,
+        storage: tile_storage,
+        texture,
+        tile_size: TilemapTileSize::new(TILE_SIZE, TILE_SIZE),
+        transform: Transform::from_xyz(0.0, 0.0, 0.0),
+        spacing,
+        material,
+        ..Default::default()
+    }
+}
+
+// only what's at the surface gets in the way, the sea floor layers are just scenery
+fn blocks_movement(depth_level: Option<DepthLevel>) -> bool {
+    match depth_level {
+        None | Some(DepthLevel::Surface) => true,
+        Some(DepthLevel::Underwater) => false,
+    }
+}
+
+// straight from the map data so headless runs (the replay test) collide the same as the game
+pub fn build_tile_collision_map(map: &Map) -> TileCollisionMap {
+    let mut collision_map = TileCollisionMap::new(map_tilemap_size(map));
+    for (tile_pos, navigation) in navigable_tiles(map) {
+        collision_map.set_solid(&tile_pos, navigation.cost.is_none());
+    }
+    collision_map
+}
+
+// what update_pathfinding_grid ends up with once the map's tiles have spawned, for headless runs
+pub fn build_pathfinding_grid(map: &Map) -> PathfindingGrid {
+    let mut grid = PathfindingGrid::new(map_tilemap_size(map));
+    for (tile_pos, navigation) in navigable_tiles(map) {
+        grid.set_cost(&tile_pos, navigation.cost);
+    }
+    grid
+}
+
+fn map_tilemap_size(map: &Map) -> TilemapSize {
+    TilemapSize {
+        x: map.width,
+        y: map.height,
+    }
+}
+
+// every tile on the layers that block movement, the ones process_tile_layer gives a TileNavigation
+fn navigable_tiles(map: &Map) -> Vec<(TilePos, TileNavigation)> {
+    let map_size = map_tilemap_size(map);
+    let mut tiles = Vec::new();
+    for layer in map.layers() {
+        if !blocks_movement(layer_depth_level(&layer)) {
+            continue;
+        }
+        let LayerType::Tiles(TileLayer::Finite(layer_data)) = layer.layer_type() else {
+            continue;
+        };
+        for x in 0..map_size.x {
+            for y in 0..map_size.y {
+                if let Some(layer_tile) = layer_data.get_tile(x as i32, y as i32) {
+                    tiles.push((TilePos { x, y }, tile_navigation(&layer_tile)));
+                }
+            }
+        }
+    }
+    tiles
+}
+
+fn layer_depth_level(layer: &Layer) -> Option<DepthLevel> {
+    match layer.properties.get("depth") {
+        Some(PropertyValue::StringValue(depth)) if depth == "surface" => Some(DepthLevel::Surface),
+        Some(PropertyValue::StringValue(depth)) if depth == "underwater" => {
+            Some(DepthLevel::Underwater)
+        }
+        Some(other) => {
+            info!(
+                "Ignoring unknown depth property {:?} on layer {}",
+                other,
+                layer.id()
+            );
+            None
+        }
+        None => None,
+    }
 }
 
 fn process_tile_layer(
@@ -104,6 +273,7 @@ fn process_tile_layer(
     tile_layer: TileLayer,
     map_size: TilemapSize,
     tilemap_id: TilemapId,
+    navigable: bool,
 ) -> TileStorage {
     let TileLayer::Finite(layer_data) = tile_layer else {
         info!("Skipping layer because only finite layers are supported.");
@@ -118,6 +288,13 @@ fn process_tile_layer(
                 let texture_index = layer_tile.id();
                 let tile_pos = TilePos { x, y };
                 let tile_entity = create_tile_entity(commands, tile_pos, tilemap_id, texture_index);
+                if navigable {
+                    let navigation = tile_navigation(&layer_tile);
+                    if navigation.cost.is_some() {
+                        commands.entity(tile_entity).insert(WaterTileTag);
+                    }
+                    commands.entity(tile_entity).insert(navigation);
+                }
                 tile_storage.set(&tile_pos, tile_entity);
             }
         }
@@ -126,6 +303,46 @@ fn process_tile_layer(
     tile_storage
 }
 
+// tiled object coordinates are in map pixels from the map corner, tiles are centered on their TilePos
+// in the world, hence the half tile shift
+pub fn tiled_object_world_position(object: &ObjectData) -> Vec2 {
+    Vec2::new(object.x - TILE_SIZE / 2.0, object.y - TILE_SIZE / 2.0)
+}
+
+pub fn float_property(properties: &Properties, name: &str) -> Option<f32> {
+    match properties.get(name) {
+        Some(PropertyValue::FloatValue(value)) => Some(*value),
+        Some(PropertyValue::IntValue(value)) => Some(*value as f32),
+        _ => None,
+    }
+}
+
+// tiled colors come in as 0-255 channels, alpha is dropped
+pub fn color_property(properties: &Properties, name: &str) -> Option<Vec3> {
+    match properties.get(name) {
+        Some(PropertyValue::ColorValue(color)) => {
+            Some(Vec3::new(color.red as f32, color.green as f32, color.blue as f32) / 255.0)
+        }
+        _ => None,
+    }
+}
+
+// `solid = true` tiles block, everything else costs its `cost` tile property (1 by default) to cross
+fn tile_navigation(layer_tile: &LayerTile) -> TileNavigation {
+    let Some(tile) = layer_tile.get_tile() else {
+        return TileNavigation::default();
+    };
+    if matches!(
+        tile.properties.get("solid"),
+        Some(PropertyValue::BoolValue(true))
+    ) {
+        return TileNavigation { cost: None };
+    }
+    TileNavigation {
+        cost: Some(float_property(&tile.properties, "cost").unwrap_or(1.0)),
+    }
+}
+
 fn create_tile_entity(
     commands: &mut Commands,
     tile_pos: TilePos,
@@ -163,10 +380,64 @@ fn create_tile_entity(
     entity_builder.id()
 }
 
+// surface layers hide once the player is under and the sea floor layers show through instead
+pub fn update_depth_layer_visibility(
+    player_query: Query<&DepthComponent, With<PlayerEntityTag>>,
+    mut layer_query: Query<(&TilemapDepthLayer, &mut Visibility)>,
+) {
+    // TODO: with more than one player this should be per camera
+    let submerged = player_query
+        .iter()
+        .next()
+        .is_some_and(|depth| !depth.at_surface());
+
+    for (depth_layer, mut visibility) in layer_query.iter_mut() {
+        let shown = match depth_layer.0 {
+            DepthLevel::Surface => !submerged,
+            DepthLevel::Underwater => submerged,
+        };
+        *visibility = if shown {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
 // SHADER STUFF:
 
-pub fn update_time_on_shader(time: Res<Time>, mut materials: ResMut<Assets<FogMaterial>>) {
-    for (_, material) in materials.iter_mut() {
-        material.time += time.delta_seconds();
+pub fn update_time_on_shader(
+    time: Res<Time>,
+    mut fog_materials: ResMut<Assets<FogMaterial>>,
+    mut water_materials: ResMut<Assets<WaterMaterial>>,
+) {
+    for fog in tilemap_fogs(&mut fog_materials, &mut water_materials) {
+        fog.time += time.delta_seconds();
+    }
+}
+
+// the moon sits on the top screen, so the glint lines up with where it is on that screen: straight
+// up the water camera's view from its bottom edge, shifted sideways as far as the moon is off center
+pub fn update_water_glint(
+    moon_query: Query<&MoonLightSource, With<MoonTag>>,
+    top_camera_query: Query<&GlobalTransform, With<TopCameraTag>>,
+    bottom_camera_query: Query<(&GlobalTransform, &OrthographicProjection), With<BottomCameraTag>>,
+    mut water_materials: ResMut<Assets<WaterMaterial>>,
+) {
+    let (Some(moonlight), Some(top_camera), Some((bottom_camera, projection))) = (
+        moon_query.iter().next(),
+        top_camera_query.iter().next(),
+        bottom_camera_query.iter().next(),
+    ) else {
+        return;
+    };
+    let moon_offset_x = moonlight.position.x - top_camera.translation().x;
+    let view = camera_view(bottom_camera, projection);
+
+    for (_, material) in water_materials.iter_mut() {
+        material.glint_origin = Vec2::new(view.center().x + moon_offset_x, view.min.y);
+        material.glint_length = view.height();
+        material.glint_intensity = moonlight.intensity;
+        material.glint_color = moonlight.color;
     }
 }
@@ -1,25 +1,57 @@
 use bevy::{math::Vec3, reflect::TypePath};
-use bevy_asset::Asset;
+use bevy_asset::{Asset, Handle};
 use bevy_ecs_tilemap::prelude::MaterialTilemap;
-use bevy_render::render_resource::{AsBindGroup, ShaderRef};
+use bevy_render::{
+    render_resource::{AsBindGroup, ShaderRef, ShaderType},
+    texture::Image,
+};
 
-#[derive(AsBindGroup, TypePath, Debug, Default, Clone, Asset)]
-pub struct FogMaterial {
-    #[uniform(0)]
-    pub time: f32,
+use crate::lighting::lighting_res::GpuLight2d;
 
-    #[uniform(0)]
+// everything the fog on top of a tilemap needs, shared by FogMaterial and WaterMaterial so the
+// day/night, weather and shader time systems drive both the same way. keep in sync with Fog in
+// tile_common.wgsl
+#[derive(ShaderType, Debug, Clone, Copy)]
+pub struct FogUniform {
+    pub time: f32,
     pub density: f32,
-
-    #[uniform(0)]
     pub fog_color: Vec3,
+    pub wind_dir: Vec3,
+    // how far the wind has pushed the fog so far, accumulated on the cpu so a change in wind
+    // strength doesn't make the whole fog jump
+    pub wind_offset: Vec3,
+    // multiplied onto the tiles before the fog goes on top, white leaves them untouched
+    pub tint: Vec3,
+}
 
+impl Default for FogUniform {
+    fn default() -> Self {
+        Self {
+            time: 0.0,
+            density: 0.5,
+            fog_color: Vec3::new(1.0, 1.0, 1.0),
+            wind_dir: Vec3::new(1.0, 0.0, 0.0),
+            wind_offset: Vec3::ZERO,
+            tint: Vec3::ONE,
+        }
+    }
+}
+
+#[derive(AsBindGroup, TypePath, Debug, Default, Clone, Asset)]
+pub struct FogMaterial {
     #[uniform(0)]
-    pub wind_dir: Vec3,
+    pub fog: FogUniform,
 
-    // Padding to ensure 16-byte alignment (required by WGSL)
+    // 0 shades the tiles as flat, 1 uses the normal map as is
     #[uniform(0)]
-    pub _padding: Vec3,
+    pub normal_map_strength: f32,
+
+    #[storage(1, read_only)]
+    pub lights: Vec<GpuLight2d>,
+
+    #[texture(2)]
+    #[sampler(3)]
+    pub normal_map: Option<Handle<Image>>,
 }
 
 impl MaterialTilemap for FogMaterial {
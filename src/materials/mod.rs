@@ -1,3 +1,5 @@
 pub mod fog;
 pub mod mode7;
+pub mod moon_phase;
 pub mod reflections;
+pub mod water;
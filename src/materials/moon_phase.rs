@@ -0,0 +1,32 @@
+use bevy::{
+    math::Vec2,
+    prelude::TypePath,
+    render::render_resource::{AsBindGroup, ShaderRef},
+    sprite::Material2d,
+};
+use bevy_asset::{Asset, Handle};
+use bevy_render::texture::Image;
+
+#[derive(AsBindGroup, TypePath, Debug, Clone, Asset)]
+pub struct MoonPhaseMaterial {
+    // 0 is a new moon, 0.5 full and back to new at 1
+    #[uniform(0)]
+    pub phase: f32,
+    // how much of the unlit side still shows
+    #[uniform(0)]
+    pub earthshine: f32,
+    // the moon's disc within the texture in uv units, anything outside it is left alone
+    #[uniform(0)]
+    pub disc_center: Vec2,
+    #[uniform(0)]
+    pub disc_radius: f32,
+    #[texture(1)]
+    #[sampler(2)]
+    pub texture: Handle<Image>,
+}
+
+impl Material2d for MoonPhaseMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/moon_phase.wgsl".into()
+    }
+}
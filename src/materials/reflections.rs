@@ -1,5 +1,4 @@
 use bevy::{
-    math::{Vec2, Vec3},
     prelude::TypePath,
     render::render_resource::{AsBindGroup, ShaderRef},
     sprite::Material2d,
@@ -7,17 +6,22 @@ use bevy::{
 use bevy_asset::{Asset, Handle};
 use bevy_render::texture::Image;
 
+use crate::lighting::lighting_res::GpuLight2d;
+
 #[derive(AsBindGroup, TypePath, Debug, Clone, Asset)]
 pub struct ReflectionMaterial {
+    // 0 shades the texture as flat, 1 uses the normal map as is
     #[uniform(0)]
-    pub light_position: Vec2,
-    #[uniform(0)]
-    pub light_intensity: f32,
-    #[uniform(0)]
-    pub light_color: Vec3,
+    pub normal_map_strength: f32,
     #[texture(1)]
     #[sampler(2)]
     pub texture: Handle<Image>,
+    // kept up to date by apply_lights_to_materials
+    #[storage(3, read_only)]
+    pub lights: Vec<GpuLight2d>,
+    #[texture(4)]
+    #[sampler(5)]
+    pub normal_map: Option<Handle<Image>>,
 }
 
 impl Material2d for ReflectionMaterial {
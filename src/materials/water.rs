@@ -0,0 +1,75 @@
+use bevy::{
+    math::{Vec2, Vec3},
+    reflect::TypePath,
+};
+use bevy_asset::{Asset, Assets, Handle};
+use bevy_ecs_tilemap::prelude::MaterialTilemap;
+use bevy_render::{
+    render_resource::{AsBindGroup, ShaderRef},
+    texture::Image,
+};
+
+use crate::{
+    lighting::lighting_res::GpuLight2d,
+    materials::fog::{FogMaterial, FogUniform},
+};
+
+// the surface tilemap: lit and fogged like FogMaterial, with the moon's reflection glinting on the
+// water in between
+#[derive(AsBindGroup, TypePath, Debug, Default, Clone, Asset)]
+pub struct WaterMaterial {
+    #[uniform(0)]
+    pub fog: FogUniform,
+
+    // 0 shades the tiles as flat, 1 uses the normal map as is
+    #[uniform(0)]
+    pub normal_map_strength: f32,
+
+    // world position straight below the moon as the water camera sees it, the glint path runs
+    // up the screen from here towards the moon
+    #[uniform(0)]
+    pub glint_origin: Vec2,
+
+    // half width of the glint path in world units
+    #[uniform(0)]
+    pub glint_width: f32,
+
+    // how far up the screen the glint path reaches
+    #[uniform(0)]
+    pub glint_length: f32,
+
+    // the moon's light intensity, 0 turns the glint off
+    #[uniform(0)]
+    pub glint_intensity: f32,
+
+    #[uniform(0)]
+    pub glint_color: Vec3,
+
+    #[storage(1, read_only)]
+    pub lights: Vec<GpuLight2d>,
+
+    #[texture(2)]
+    #[sampler(3)]
+    pub normal_map: Option<Handle<Image>>,
+}
+
+impl MaterialTilemap for WaterMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/water.wgsl".into()
+    }
+}
+
+// the fog settings of every tilemap material, for the systems that drive the fog
+pub fn tilemap_fogs<'a>(
+    fog_materials: &'a mut Assets<FogMaterial>,
+    water_materials: &'a mut Assets<WaterMaterial>,
+) -> impl Iterator<Item = &'a mut FogUniform> {
+    fog_materials
+        .iter_mut()
+        .map(|(_, material)| &mut material.fog)
+        .chain(
+            water_materials
+                .iter_mut()
+                .map(|(_, material)| &mut material.fog),
+        )
+}
@@ -1 +1,2 @@
+pub mod player_res;
 pub mod player_sys;
@@ -0,0 +1,28 @@
+use bevy::prelude::Resource;
+
+use crate::{COOP_MERGE_DISTANCE, COOP_SPLIT_DISTANCE};
+
+// `--coop` spawns a second player, the top screen follows them and the bottom screen player one
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct CoopSettings {
+    pub enabled: bool,
+    // closer than this the bottom screen takes over the whole window and frames both players
+    pub merge_distance: f32,
+    // has to be larger than merge_distance so the view doesn't flicker at the boundary
+    pub split_distance: f32,
+}
+
+impl Default for CoopSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            merge_distance: COOP_MERGE_DISTANCE,
+            split_distance: COOP_SPLIT_DISTANCE,
+        }
+    }
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct CoopView {
+    pub merged: bool,
+}
@@ -1,25 +1,47 @@
+use std::env;
+
 use bevy::{
     core::Name,
-    input::ButtonInput,
-    math::{UVec2, Vec3},
+    log::info,
+    math::UVec2,
     prelude::{
-        Assets, Commands, Fixed, KeyCode, Query, Res, ResMut, TextureAtlasLayout, Time, Transform,
-        With,
+        Assets, Commands, Entity, EventWriter, Fixed, Query, Res, ResMut, TextureAtlasLayout, Time,
+        Transform, With,
     },
     sprite::{SpriteBundle, TextureAtlas},
 };
 
 use crate::{
-    anime::anime_res::PlayerEntityAnimationAssets,
+    anime::{anime_components::WakeEmitter, anime_res::PlayerEntityAnimationAssets},
     bundles::PlayerBundle,
-    kinetic_components::{KineticEntityComponents, PlayerEntityTag},
-    DEFAULT_SPEED, PLAYER_ENTITY_ANIMATION_TEXTURE_START_IDX, PLAYER_ENTITY_SPAWN_X,
-    PLAYER_ENTITY_SPAWN_Y, PLAYER_ENTITY_Z_LEVEL, TILE_SIZE,
+    input::{
+        input_components::{InputActions, InputBindings, PointerSteeringTag},
+        input_res::InputConfig,
+    },
+    kinetic_components::{
+        DepthComponent, JumpComponent, KineticEntityComponents, PlayerEntityTag, PlayerSlot,
+        SteeringInput, TileCollider,
+    },
+    kinetic_res::JumpSettings,
+    kinetic_sys::{dive_step, jump_step, SplashEvent},
+    player::player_res::CoopSettings,
+    COOP_PLAYER_COUNT, COOP_PLAYER_TWO_SPAWN_OFFSET_X, PLAYER_ENTITY_ANIMATION_TEXTURE_START_IDX,
+    PLAYER_ENTITY_SPAWN_X, PLAYER_ENTITY_SPAWN_Y, PLAYER_ENTITY_Z_LEVEL, TILE_SIZE,
 };
 
+// `--coop` on the command line turns on the second player
+pub fn setup_coop_from_args(mut coop_settings: ResMut<CoopSettings>) {
+    if env::args().any(|arg| arg == "--coop") {
+        info!("Local co-op enabled");
+        coop_settings.enabled = true;
+    }
+}
+
 pub fn spawn_player_entity(
     mut commands: Commands,
     player_assets: Res<PlayerEntityAnimationAssets>,
+    input_config: Res<InputConfig>,
+    coop_settings: Res<CoopSettings>,
     mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
 ) {
     let vehicle_animation_image_handle = player_assets.image_handle.clone();
@@ -32,91 +54,121 @@ pub fn spawn_player_entity(
         None,
     ));
 
-    let texture_atlas = TextureAtlas {
-        layout: vehicle_texture_atlas_layout.clone(),
-        index: PLAYER_ENTITY_ANIMATION_TEXTURE_START_IDX,
+    let player_count = if coop_settings.enabled {
+        COOP_PLAYER_COUNT
+    } else {
+        1
     };
 
-    // TODO: 0,0 should not be bottom left
-    //  anymore it should be top left
-    //  review CRT scanline order and latin writing conventions (japan didn't invent the computer)
+    for slot in 0..player_count {
+        let texture_atlas = TextureAtlas {
+            layout: vehicle_texture_atlas_layout.clone(),
+            index: PLAYER_ENTITY_ANIMATION_TEXTURE_START_IDX,
+        };
 
-    let transform = Transform::from_xyz(
-        PLAYER_ENTITY_SPAWN_X * TILE_SIZE,
-        PLAYER_ENTITY_SPAWN_Y * TILE_SIZE,
-        PLAYER_ENTITY_Z_LEVEL,
-    );
+        // TODO: 0,0 should not be bottom left
+        //  anymore it should be top left
+        //  review CRT scanline order and latin writing conventions (japan didn't invent the computer)
 
-    let sprite_sheet = SpriteBundle {
-        texture: vehicle_animation_image_handle.clone(),
-        transform,
-        ..Default::default()
-    };
+        let spawn_offset_x = slot as f32 * COOP_PLAYER_TWO_SPAWN_OFFSET_X;
+        let transform = Transform::from_xyz(
+            (PLAYER_ENTITY_SPAWN_X + spawn_offset_x) * TILE_SIZE,
+            PLAYER_ENTITY_SPAWN_Y * TILE_SIZE,
+            PLAYER_ENTITY_Z_LEVEL,
+        );
 
-    let player_kinetics = KineticEntityComponents {
-        x_axis_displacement: 0.0,
-        y_axis_displacement: 0.0,
-        position: transform.translation,
-        prev_position: transform.translation,
-    };
+        let sprite_sheet = SpriteBundle {
+            texture: vehicle_animation_image_handle.clone(),
+            transform,
+            ..Default::default()
+        };
+
+        let player_kinetics = KineticEntityComponents {
+            x_axis_displacement: 0.0,
+            y_axis_displacement: 0.0,
+            position: transform.translation,
+            prev_position: transform.translation,
+            heading: 0.0,
+        };
+
+        // a config with a single entry still gets a usable second player
+        let input_bindings =
+            input_config
+                .players
+                .get(slot)
+                .cloned()
+                .unwrap_or_else(|| match slot {
+                    0 => InputBindings::default(),
+                    _ => InputBindings::player_two(),
+                });
 
-    commands
-        .spawn(PlayerBundle {
-            name: Name::new("Player Entity"),
+        let mut player = commands.spawn(PlayerBundle {
+            name: Name::new(format!("Player Entity {}", slot + 1)),
             kinetics: player_kinetics,
             sprite_sheet,
             texture_atlas,
-        })
-        .insert(PlayerEntityTag);
+        });
+        player
+            .insert(PlayerEntityTag)
+            .insert(PlayerSlot(slot))
+            .insert(input_bindings.steering_mode)
+            .insert(SteeringInput::default())
+            .insert(DepthComponent::default())
+            .insert(JumpComponent::default())
+            .insert(TileCollider)
+            .insert(InputActions::default())
+            .insert(input_bindings)
+            .insert(WakeEmitter::default());
+        // the stylus lives on the bottom screen, which belongs to player one
+        if slot == 0 {
+            player.insert(PointerSteeringTag);
+        }
+    }
 }
 
+// what control_player_entity reads and steers on every player
+type PlayerControlData = (
+    Entity,
+    &'static KineticEntityComponents,
+    &'static mut SteeringInput,
+    &'static mut InputActions,
+    Option<&'static mut DepthComponent>,
+    Option<&'static mut JumpComponent>,
+);
+
+// turns the player's actions into steering for integrate_kinetic_entities, which has to run after
 pub fn control_player_entity(
     fixed_time: Res<Time<Fixed>>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut query: Query<(&mut Transform, &mut KineticEntityComponents), With<PlayerEntityTag>>,
-) {
-    for (mut transform, mut player_entity) in query.iter_mut() {
-        let a = fixed_time.overstep_fraction();
-        transform.translation = player_entity.prev_position.lerp(player_entity.position, a);
-        process_input(&keyboard_input, &mut player_entity);
-        player_entity.prev_position = player_entity.position;
-        let position_displacement = Vec3 {
-            x: player_entity.x_axis_displacement,
-            y: player_entity.y_axis_displacement,
-            z: 0.0, // TODO: Ugly, but only because the Vec3 is required to fit with the implementation
-        };
-        player_entity.position += position_displacement * fixed_time.delta_seconds();
-    }
-}
-
-fn process_input(
-    keyboard_input: &Res<ButtonInput<KeyCode>>,
-    player_entity: &mut KineticEntityComponents,
+    jump_settings: Res<JumpSettings>,
+    mut splash_writer: EventWriter<SplashEvent>,
+    mut query: Query<PlayerControlData, With<PlayerEntityTag>>,
 ) {
-    handle_y_axis_movement(keyboard_input, player_entity);
-    handle_x_axis_movement(keyboard_input, player_entity);
-}
+    for (entity, player_entity, mut steering_input, mut actions, depth, jump) in query.iter_mut() {
+        steering_input.0 = actions.move_axis;
 
-fn handle_y_axis_movement(
-    keyboard_input: &Res<ButtonInput<KeyCode>>,
-    vehicle_component: &mut KineticEntityComponents,
-) {
-    if keyboard_input.pressed(KeyCode::KeyW) {
-        vehicle_component.y_axis_displacement = DEFAULT_SPEED;
-    } else if keyboard_input.pressed(KeyCode::KeyS) {
-        vehicle_component.y_axis_displacement = -DEFAULT_SPEED;
-    } else {
-        vehicle_component.y_axis_displacement = 0.0;
+        let airborne = jump.as_ref().is_some_and(|jump| jump.is_airborne());
+        let at_surface = depth.as_ref().is_none_or(|depth| depth.at_surface());
+        if let Some(mut jump) = jump {
+            let launch = actions.jump.just_pressed && at_surface;
+            if jump_step(
+                &mut jump,
+                launch,
+                &jump_settings,
+                fixed_time.delta_seconds(),
+            ) {
+                splash_writer.send(SplashEvent {
+                    entity,
+                    position: player_entity.position,
+                });
+            }
+        }
+        if let Some(mut depth) = depth {
+            dive_step(
+                &mut depth,
+                actions.dive.just_pressed && !airborne,
+                fixed_time.delta_seconds(),
+            );
+        }
+        actions.consume_just_pressed();
     }
 }
-
-fn handle_x_axis_movement(
-    keyboard_input: &Res<ButtonInput<KeyCode>>,
-    vehicle_component: &mut KineticEntityComponents,
-) {
-    let strafe_right = keyboard_input.pressed(KeyCode::KeyD) as i32;
-    let strafe_left = keyboard_input.pressed(KeyCode::KeyA) as i32;
-    let strafe_direction = (strafe_right - strafe_left) as f32; // 1 if D is pressed, -1 if A is pressed, 0 otherwise
-
-    vehicle_component.x_axis_displacement = DEFAULT_SPEED * strafe_direction;
-}
@@ -0,0 +1,2 @@
+pub mod replay_res;
+pub mod replay_sys;
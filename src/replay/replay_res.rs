@@ -0,0 +1,146 @@
+use std::{fs, io, path::PathBuf};
+
+use bevy::{
+    math::{Vec2, Vec3},
+    prelude::Resource,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    environment::weather::WeatherKind, input::input_components::InputActions,
+    kinetic_components::SteeringMode,
+};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReplayData {
+    pub seed: u64,
+    pub fixed_timestep_seconds: f64,
+    pub steering_mode: SteeringMode,
+    pub start_position: Vec3,
+    // the actions of every FixedUpdate tick in order, run length encoded since most ticks repeat
+    // the one before
+    pub inputs: Vec<ReplayInput>,
+    // the simulation state every REPLAY_CHECKPOINT_TICKS ticks, so playback can tell where it
+    // started to diverge instead of only that it ended up somewhere else
+    pub checkpoints: Vec<ReplayCheckpoint>,
+    pub final_position: Vec3,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReplayInput {
+    pub ticks: u32,
+    pub actions: InputActions,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReplayCheckpoint {
+    // ticks simulated so far
+    pub tick: u32,
+    pub player_position: Vec3,
+    // the next value GameRng would hand out, equal only if every roll so far was the same
+    pub rng: u64,
+    // every environment entity, sorted so spawn order doesn't matter
+    pub environment_positions: Vec<Vec3>,
+    pub weather: WeatherKind,
+    // how far the wind has pushed the fog, catches a weather change easing in at the wrong pace
+    pub wind_offset: Vec2,
+}
+
+impl ReplayData {
+    pub fn new(seed: u64, fixed_timestep_seconds: f64) -> Self {
+        Self {
+            seed,
+            fixed_timestep_seconds,
+            steering_mode: SteeringMode::default(),
+            start_position: Vec3::ZERO,
+            inputs: Vec::new(),
+            checkpoints: Vec::new(),
+            final_position: Vec3::ZERO,
+        }
+    }
+
+    pub fn load(path: &PathBuf) -> Result<Self, ReplayError> {
+        let contents = fs::read_to_string(path)?;
+        ron::from_str(&contents).map_err(|e| ReplayError::Parse(e.to_string()))
+    }
+
+    pub fn save(&self, path: &PathBuf) -> Result<(), ReplayError> {
+        // one line per input run and per checkpoint keeps rerecorded replays diffable
+        let pretty_config = ron::ser::PrettyConfig::default().depth_limit(2);
+        let contents = ron::ser::to_string_pretty(self, pretty_config)
+            .map_err(|e| ReplayError::Serialize(e.to_string()))?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    pub fn tick_count(&self) -> u32 {
+        self.inputs.iter().map(|input| input.ticks).sum()
+    }
+
+    pub fn push_actions(&mut self, actions: InputActions) {
+        match self.inputs.last_mut() {
+            Some(last) if last.actions == actions => last.ticks += 1,
+            _ => self.inputs.push(ReplayInput { ticks: 1, actions }),
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct ReplayRecorder {
+    pub path: PathBuf,
+    pub data: ReplayData,
+}
+
+#[derive(Resource)]
+pub struct ReplayPlayback {
+    pub data: ReplayData,
+    pub tick: u32,
+    // which run of inputs the next tick reads from, and how far into it playback is
+    pub input_index: usize,
+    pub input_tick: u32,
+    pub result_reported: bool,
+}
+
+impl ReplayPlayback {
+    pub fn new(data: ReplayData) -> Self {
+        Self {
+            data,
+            tick: 0,
+            input_index: 0,
+            input_tick: 0,
+            result_reported: false,
+        }
+    }
+
+    pub fn finished(&self) -> bool {
+        self.input_index >= self.data.inputs.len()
+    }
+
+    pub fn next_actions(&mut self) -> Option<InputActions> {
+        let input = self.data.inputs.get(self.input_index)?;
+        let actions = input.actions;
+        self.input_tick += 1;
+        if self.input_tick >= input.ticks {
+            self.input_index += 1;
+            self.input_tick = 0;
+        }
+        self.tick += 1;
+        Some(actions)
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ReplayError {
+    #[error("IO Error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Replay Parsing Error: {0}")]
+    Parse(String),
+
+    #[error("Replay Serializing Error: {0}")]
+    Serialize(String),
+}
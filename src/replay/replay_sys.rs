@@ -0,0 +1,168 @@
+use std::{env, path::PathBuf};
+
+use bevy::{
+    app::AppExit,
+    log::{info, warn},
+    math::Vec3,
+    prelude::{Commands, EventReader, Fixed, Query, Res, ResMut, Time, With},
+};
+
+use crate::{
+    environment::weather::Weather,
+    input::input_components::InputActions,
+    kinetic_components::{EnvironmentEntityTag, KineticEntityComponents, PlayerSlot, SteeringMode},
+    replay::replay_res::{ReplayCheckpoint, ReplayData, ReplayPlayback, ReplayRecorder},
+    rng_res::GameRng,
+    REPLAY_CHECKPOINT_TICKS,
+};
+
+// `--record <path>` records the session into a replay file on exit,
+// `--replay <path>` drives the player from a replay file instead of the input devices
+pub fn setup_replay_from_args(mut commands: Commands, fixed_time: Res<Time<Fixed>>) {
+    let args: Vec<String> = env::args().collect();
+    let arg_value = |flag: &str| {
+        args.iter()
+            .position(|arg| arg == flag)
+            .and_then(|i| args.get(i + 1))
+            .map(PathBuf::from)
+    };
+
+    if let Some(path) = arg_value("--replay") {
+        match ReplayData::load(&path) {
+            Ok(data) => {
+                info!(
+                    "Playing back replay {:?} ({} ticks)",
+                    path,
+                    data.tick_count()
+                );
+                commands.insert_resource(GameRng::from_seed(data.seed));
+                commands.insert_resource(Time::<Fixed>::from_seconds(data.fixed_timestep_seconds));
+                commands.insert_resource(ReplayPlayback::new(data));
+                return;
+            }
+            Err(e) => warn!("Failed to load replay {:?}, playing normally: {}", path, e),
+        }
+    }
+
+    let seed = rand::random();
+    commands.insert_resource(GameRng::from_seed(seed));
+
+    if let Some(path) = arg_value("--record") {
+        info!("Recording replay to {:?}", path);
+        commands.insert_resource(ReplayRecorder {
+            path,
+            data: ReplayData::new(seed, fixed_time.timestep().as_secs_f64()),
+        });
+    }
+}
+
+// has to run in FixedUpdate before the actions get consumed by control_player_entity, after
+// play_replay_tick so a replay being played back can be recorded again
+pub fn record_replay_tick(
+    mut recorder: ResMut<ReplayRecorder>,
+    query: Query<(
+        &KineticEntityComponents,
+        &InputActions,
+        Option<&SteeringMode>,
+        &PlayerSlot,
+    )>,
+) {
+    // TODO: only player one gets recorded
+    let Some((kinetics, actions, steering_mode, _)) = query.iter().find(|(.., slot)| slot.0 == 0)
+    else {
+        return;
+    };
+    if recorder.data.inputs.is_empty() {
+        recorder.data.start_position = kinetics.position;
+        recorder.data.steering_mode = steering_mode.copied().unwrap_or_default();
+    }
+    recorder.data.push_actions(*actions);
+}
+
+// has to run in FixedUpdate at the end of the tick, once everything has moved
+pub fn record_replay_checkpoint(
+    mut recorder: ResMut<ReplayRecorder>,
+    game_rng: Res<GameRng>,
+    weather: Res<Weather>,
+    query: Query<(&KineticEntityComponents, &PlayerSlot)>,
+    environment_query: Query<&KineticEntityComponents, With<EnvironmentEntityTag>>,
+) {
+    let Some((kinetics, _)) = query.iter().find(|(_, slot)| slot.0 == 0) else {
+        return;
+    };
+    recorder.data.final_position = kinetics.position;
+    let tick = recorder.data.tick_count();
+    if tick.is_multiple_of(REPLAY_CHECKPOINT_TICKS) {
+        let mut environment_positions: Vec<Vec3> = environment_query
+            .iter()
+            .map(|environment| environment.position)
+            .collect();
+        environment_positions.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+        recorder.data.checkpoints.push(ReplayCheckpoint {
+            tick,
+            player_position: kinetics.position,
+            rng: game_rng.peek(),
+            environment_positions,
+            weather: weather.kind,
+            wind_offset: weather.wind_offset,
+        });
+    }
+}
+
+pub fn save_replay_on_exit(mut exit_reader: EventReader<AppExit>, recorder: Res<ReplayRecorder>) {
+    if exit_reader.read().next().is_none() {
+        return;
+    }
+    match recorder.data.save(&recorder.path) {
+        Ok(()) => info!(
+            "Saved replay {:?} ({} ticks)",
+            recorder.path,
+            recorder.data.tick_count()
+        ),
+        Err(e) => warn!("Failed to save replay {:?}: {}", recorder.path, e),
+    }
+}
+
+// has to run in FixedUpdate before control_player_entity, after gather_input_actions
+pub fn play_replay_tick(
+    mut playback: ResMut<ReplayPlayback>,
+    mut query: Query<(
+        &mut KineticEntityComponents,
+        &mut InputActions,
+        Option<&mut SteeringMode>,
+        &PlayerSlot,
+    )>,
+) {
+    let Some((mut kinetics, mut actions, steering_mode, _)) =
+        query.iter_mut().find(|(.., slot)| slot.0 == 0)
+    else {
+        return;
+    };
+
+    if playback.tick == 0 {
+        kinetics.position = playback.data.start_position;
+        kinetics.prev_position = playback.data.start_position;
+        if let Some(mut steering_mode) = steering_mode {
+            *steering_mode = playback.data.steering_mode;
+        }
+    }
+
+    *actions = playback.next_actions().unwrap_or_default();
+}
+
+// has to run in FixedUpdate after integrate_kinetic_entities
+pub fn report_replay_result(
+    mut playback: ResMut<ReplayPlayback>,
+    query: Query<(&KineticEntityComponents, &PlayerSlot)>,
+) {
+    if !playback.finished() || playback.result_reported {
+        return;
+    }
+    if let Some((kinetics, _)) = query.iter().find(|(_, slot)| slot.0 == 0) {
+        info!(
+            "Replay finished at {:?}, recorded final position was {:?}",
+            kinetics.position, playback.data.final_position
+        );
+    }
+    playback.result_reported = true;
+}
@@ -0,0 +1,34 @@
+use bevy::prelude::Resource;
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+// every gameplay random roll should come from here so a replay can reproduce it from the seed
+#[derive(Resource)]
+pub struct GameRng {
+    pub seed: u64,
+    pub rng: StdRng,
+}
+
+impl GameRng {
+    pub fn from_seed(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    // the next value a roll would get, without using it up. two runs that rolled the same things
+    // peek the same value, which is what replays check against
+    pub fn peek(&self) -> u64 {
+        self.rng.clone().next_u64()
+    }
+}
+
+// for purely cosmetic rolls (rain, sparkles) that can run per frame without desyncing a replay
+#[derive(Resource)]
+pub struct EffectsRng(pub StdRng);
+
+impl Default for EffectsRng {
+    fn default() -> Self {
+        Self(StdRng::from_entropy())
+    }
+}
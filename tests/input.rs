@@ -0,0 +1,145 @@
+// the input action layer without real devices: key/gamepad state is written straight into the
+// bevy input resources, and gameplay is driven by writing InputActions directly
+use std::{env, fs, path::Path, time::Duration};
+
+use bevy::{
+    ecs::system::RunSystemOnce,
+    input::{
+        gamepad::{GamepadAxis, GamepadButton},
+        keyboard::KeyCode,
+        Axis, ButtonInput,
+    },
+    math::{Vec2, Vec3},
+    prelude::{Entity, Events, Fixed, Time, Transform, World},
+};
+use yakuzaishi::{
+    input::{
+        input_components::{InputActions, InputBindings},
+        input_res::InputConfig,
+        input_sys::gather_input_actions,
+    },
+    kinetic_components::{KineticEntityComponents, PlayerEntityTag, SteeringInput, SteeringMode},
+    kinetic_res::{JumpSettings, SwimPhysicsSettings},
+    kinetic_sys::{integrate_kinetic_entities, SplashEvent},
+    player::player_sys::control_player_entity,
+    INPUT_CONFIG_FILE_PATH,
+};
+
+fn input_world(pressed: &[KeyCode]) -> World {
+    let mut world = World::new();
+    let mut keyboard_input = ButtonInput::<KeyCode>::default();
+    for key in pressed {
+        keyboard_input.press(*key);
+    }
+    world.insert_resource(keyboard_input);
+    world.insert_resource(ButtonInput::<GamepadButton>::default());
+    world.insert_resource(Axis::<GamepadAxis>::default());
+    world
+}
+
+fn spawn_bound_entity(world: &mut World, bindings: InputBindings) -> Entity {
+    world.spawn((bindings, InputActions::default())).id()
+}
+
+fn arrow_bindings() -> InputBindings {
+    InputBindings {
+        move_up: vec![KeyCode::ArrowUp],
+        move_down: vec![KeyCode::ArrowDown],
+        move_left: vec![KeyCode::ArrowLeft],
+        move_right: vec![KeyCode::ArrowRight],
+        dash: vec![KeyCode::ShiftRight],
+        gamepad: None,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn rebound_keys_drive_the_move_axis() {
+    let mut world = input_world(&[KeyCode::ArrowLeft, KeyCode::ShiftRight, KeyCode::KeyW]);
+    let entity = spawn_bound_entity(&mut world, arrow_bindings());
+
+    world.run_system_once(gather_input_actions);
+
+    let actions = world.get::<InputActions>(entity).unwrap();
+    // KeyW is no longer bound to anything, only the arrows move
+    assert_eq!(actions.move_axis, Vec2::new(-1.0, 0.0));
+    assert!(actions.dash.pressed);
+    assert!(actions.dash.just_pressed);
+    assert!(!actions.dive.pressed);
+}
+
+#[test]
+fn diagonal_keys_keep_full_deflection_on_each_axis() {
+    let mut world = input_world(&[KeyCode::KeyW, KeyCode::KeyD]);
+    let entity = spawn_bound_entity(&mut world, InputBindings::default());
+
+    world.run_system_once(gather_input_actions);
+
+    // tank steering reads x as turn and y as throttle, so the diagonal must not be shortened
+    let actions = world.get::<InputActions>(entity).unwrap();
+    assert_eq!(actions.move_axis, Vec2::new(1.0, 1.0));
+}
+
+#[test]
+fn input_config_round_trips_through_a_file() {
+    let config = InputConfig {
+        players: vec![
+            InputBindings {
+                steering_mode: SteeringMode::Tank,
+                ..arrow_bindings()
+            },
+            InputBindings::default(),
+        ],
+    };
+    let path = env::temp_dir().join(format!("input_bindings_{}.ron", std::process::id()));
+
+    config.save(&path).unwrap();
+    let loaded = InputConfig::load(&path);
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.unwrap(), config);
+}
+
+#[test]
+fn shipped_input_config_parses() {
+    let config = InputConfig::load(Path::new(INPUT_CONFIG_FILE_PATH)).unwrap();
+    assert_eq!(config.players.first(), Some(&InputBindings::default()));
+}
+
+#[test]
+fn injected_actions_move_the_player() {
+    let mut world = World::new();
+    let mut fixed_time = Time::<Fixed>::default();
+    fixed_time.advance_by(Duration::from_secs_f64(1.0 / 64.0));
+    world.insert_resource(fixed_time);
+    world.insert_resource(SwimPhysicsSettings::default());
+    world.insert_resource(JumpSettings::default());
+    world.init_resource::<Events<SplashEvent>>();
+    let player = world
+        .spawn((
+            KineticEntityComponents {
+                y_axis_displacement: 0.0,
+                x_axis_displacement: 0.0,
+                position: Vec3::ZERO,
+                prev_position: Vec3::ZERO,
+                heading: 0.0,
+            },
+            PlayerEntityTag,
+            SteeringInput::default(),
+            InputActions {
+                move_axis: Vec2::new(1.0, 0.0),
+                ..Default::default()
+            },
+            Transform::default(),
+        ))
+        .id();
+
+    for _ in 0..16 {
+        world.run_system_once(control_player_entity);
+        world.run_system_once(integrate_kinetic_entities);
+    }
+
+    let kinetics = world.get::<KineticEntityComponents>(player).unwrap();
+    assert!(kinetics.position.x > 0.0);
+    assert_eq!(kinetics.position.y, 0.0);
+}
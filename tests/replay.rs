@@ -0,0 +1,267 @@
+// headless regression check for movement. every replay in tests/replays gets played back through
+// the same fixed tick the game runs, on the game's map and with its environment entities, with a
+// ReplayRecorder attached, and what gets recorded the second time has to match the file checkpoint
+// by checkpoint.
+//
+// to add a replay, record a session with `cargo run -- --record tests/replays/some_run.ron`.
+// after a change that is meant to alter gameplay, record the existing replays again from their
+// inputs with `BLESS_REPLAYS=1 cargo test --test replay` and review the diff
+use std::{env, fs, path::PathBuf, time::Duration};
+
+use bevy::{
+    app::{App, FixedUpdate, Startup},
+    asset::{Assets, Handle},
+    prelude::{Commands, Fixed, Res, ResMut, TextureAtlasLayout, Time},
+    time::TimeUpdateStrategy,
+    MinimalPlugins,
+};
+use yakuzaishi::{
+    ai::{
+        ai_res::{FlockingSettings, SpatialGrid, SteeringBehaviorSettings},
+        path_res::{PathfindingQueue, PathfindingSettings},
+    },
+    anime::anime_res::{EnvironmentEntityAnimationAssets, PlayerEntityAnimationAssets},
+    environment::{
+        day_night::TimeOfDay,
+        environment_sys::{map_environment, spawn_map_environment},
+        moon_phase::{LunarPhase, MoonPhaseChanged},
+        weather::map_weather,
+    },
+    fixed_update_sys::fixed_update_systems,
+    input::input_res::InputConfig,
+    kinetic_res::{JumpSettings, SwimPhysicsSettings},
+    kinetic_sys::SplashEvent,
+    map::tiled_sys::{build_pathfinding_grid, build_tile_collision_map},
+    player::{player_res::CoopSettings, player_sys::spawn_player_entity},
+    replay::replay_res::{ReplayData, ReplayPlayback, ReplayRecorder},
+    rng_res::GameRng,
+};
+
+const POSITION_TOLERANCE: f32 = 0.001;
+
+fn manifest_path(relative: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(relative)
+}
+
+fn replay_paths() -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(manifest_path("tests/replays"))
+        .expect("tests/replays should exist")
+        .map(|entry| entry.expect("replay dir entry").path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "ron"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty(), "no replays in tests/replays");
+    paths
+}
+
+fn load(path: &PathBuf) -> ReplayData {
+    ReplayData::load(path).unwrap_or_else(|e| panic!("failed to load {:?}: {}", path, e))
+}
+
+// plays the replay back and returns what the recorder made of it. frame_ticks is how many fixed
+// timesteps each rendered frame lasts, cycled, so uneven frame pacing can be thrown at it
+fn play_back(data: &ReplayData, frame_ticks: &[f64]) -> ReplayData {
+    let map = tiled::Loader::new()
+        .load_tmx_map(manifest_path("assets/map_data/water.tmx"))
+        .expect("the water map should load");
+    let collision_map = build_tile_collision_map(&map);
+    let pathfinding_grid = build_pathfinding_grid(&map);
+    let weather = map_weather(&map);
+    // what spawn_environment_entity does, minus the TiledMapSource that needs a renderer to load
+    let spawn_environment = move |mut commands: Commands,
+                                  assets: Res<EnvironmentEntityAnimationAssets>,
+                                  mut game_rng: ResMut<GameRng>| {
+        let map_environment = map_environment(&map, &mut game_rng);
+        spawn_map_environment(&mut commands, &assets, Handle::default(), map_environment);
+    };
+    let timestep = Duration::from_secs_f64(data.fixed_timestep_seconds);
+    let tick_count = data.tick_count();
+
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .insert_resource(Time::<Fixed>::from_duration(timestep))
+        .init_resource::<SwimPhysicsSettings>()
+        .init_resource::<JumpSettings>()
+        .init_resource::<SteeringBehaviorSettings>()
+        .init_resource::<FlockingSettings>()
+        .init_resource::<SpatialGrid>()
+        .insert_resource(pathfinding_grid)
+        .init_resource::<PathfindingQueue>()
+        .init_resource::<PathfindingSettings>()
+        .init_resource::<TimeOfDay>()
+        .init_resource::<LunarPhase>()
+        .add_event::<MoonPhaseChanged>()
+        .insert_resource(weather)
+        .add_event::<SplashEvent>()
+        .init_resource::<InputConfig>()
+        .init_resource::<CoopSettings>()
+        .init_resource::<Assets<TextureAtlasLayout>>()
+        // nothing gets drawn, the startup systems only need something to hand out
+        .insert_resource(PlayerEntityAnimationAssets {
+            image_handle: Handle::default(),
+        })
+        .insert_resource(EnvironmentEntityAnimationAssets {
+            animation_image_handle: Handle::default(),
+        })
+        .insert_resource(collision_map)
+        .insert_resource(GameRng::from_seed(data.seed))
+        .insert_resource(ReplayPlayback::new(data.clone()))
+        .insert_resource(ReplayRecorder {
+            path: PathBuf::new(),
+            data: ReplayData::new(data.seed, data.fixed_timestep_seconds),
+        })
+        .add_systems(Startup, (spawn_player_entity, spawn_environment))
+        .add_systems(FixedUpdate, fixed_update_systems());
+
+    for frame in frame_ticks.iter().cycle() {
+        let world = app.world();
+        let played = world.resource::<ReplayPlayback>().tick;
+        if played >= tick_count {
+            break;
+        }
+        // never run past the end of the replay, the recording would pick up idle ticks
+        let overstep = world.resource::<Time<Fixed>>().overstep();
+        let remaining = timestep * (tick_count - played) - overstep;
+        let frame = timestep.mul_f64(*frame).min(remaining);
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(frame));
+        app.update();
+    }
+
+    app.world_mut()
+        .remove_resource::<ReplayRecorder>()
+        .expect("the recorder is never removed")
+        .data
+}
+
+fn assert_same_run(path: &PathBuf, recorded: &ReplayData, replayed: &ReplayData) {
+    assert_eq!(replayed.seed, recorded.seed, "{:?} seed", path);
+    assert_eq!(replayed.inputs, recorded.inputs, "{:?} inputs", path);
+    assert_eq!(
+        replayed.checkpoints.len(),
+        recorded.checkpoints.len(),
+        "{:?} checkpoint count",
+        path
+    );
+    for (replayed, recorded) in replayed.checkpoints.iter().zip(recorded.checkpoints.iter()) {
+        assert_eq!(replayed.tick, recorded.tick, "{:?} checkpoint tick", path);
+        let drift = replayed.player_position.distance(recorded.player_position);
+        assert!(
+            drift <= POSITION_TOLERANCE,
+            "{:?} diverged at tick {}: player at {:?}, recorded {:?}",
+            path,
+            recorded.tick,
+            replayed.player_position,
+            recorded.player_position
+        );
+        assert_eq!(
+            replayed.rng, recorded.rng,
+            "{:?} rolled a different GameRng sequence by tick {}",
+            path, recorded.tick
+        );
+        assert_eq!(
+            replayed.weather, recorded.weather,
+            "{:?} weather at tick {}",
+            path, recorded.tick
+        );
+        let wind_drift = replayed.wind_offset.distance(recorded.wind_offset);
+        assert!(
+            wind_drift <= POSITION_TOLERANCE,
+            "{:?} wind diverged at tick {}: {:?}, recorded {:?}",
+            path,
+            recorded.tick,
+            replayed.wind_offset,
+            recorded.wind_offset
+        );
+        assert_eq!(
+            replayed.environment_positions.len(),
+            recorded.environment_positions.len(),
+            "{:?} environment entity count at tick {}",
+            path,
+            recorded.tick
+        );
+        for (replayed_position, recorded_position) in replayed
+            .environment_positions
+            .iter()
+            .zip(recorded.environment_positions.iter())
+        {
+            assert!(
+                replayed_position.distance(*recorded_position) <= POSITION_TOLERANCE,
+                "{:?} environment diverged at tick {}: {:?}, recorded {:?}",
+                path,
+                recorded.tick,
+                replayed_position,
+                recorded_position
+            );
+        }
+    }
+    let drift = replayed.final_position.distance(recorded.final_position);
+    assert!(
+        drift <= POSITION_TOLERANCE,
+        "{:?} ended at {:?}, recorded {:?}",
+        path,
+        replayed.final_position,
+        recorded.final_position
+    );
+}
+
+#[test]
+fn replays_match_their_recording() {
+    let bless = env::var_os("BLESS_REPLAYS").is_some();
+    for path in replay_paths() {
+        let recorded = load(&path);
+        let replayed = play_back(&recorded, &[1.0]);
+        if bless {
+            replayed
+                .save(&path)
+                .unwrap_or_else(|e| panic!("failed to save {:?}: {}", path, e));
+            continue;
+        }
+        assert!(
+            !recorded.checkpoints.is_empty(),
+            "{:?} has no checkpoints",
+            path
+        );
+        assert_same_run(&path, &recorded, &replayed);
+    }
+}
+
+#[test]
+fn replays_do_not_depend_on_frame_pacing() {
+    for path in replay_paths() {
+        let recorded = load(&path);
+        // slow and fast frames mixed, including ones that run no fixed tick at all
+        let replayed = play_back(&recorded, &[0.5, 2.5, 0.0, 1.0, 4.0, 0.25, 3.75]);
+        assert_eq!(replayed.tick_count(), recorded.tick_count(), "{:?}", path);
+        let steady = play_back(&recorded, &[1.0]);
+        assert_same_run(&path, &steady, &replayed);
+    }
+}
+
+#[test]
+fn replays_cover_environment_spawns() {
+    // water.tmx has an environment spawner, so the environment comparison must see it fire
+    let spawned = replay_paths().iter().any(|path| {
+        let recorded = load(path);
+        match (recorded.checkpoints.first(), recorded.checkpoints.last()) {
+            (Some(first), Some(last)) => {
+                last.environment_positions.len() > first.environment_positions.len()
+            }
+            _ => false,
+        }
+    });
+    assert!(spawned, "no replay records an environment spawner firing");
+}
+
+#[test]
+fn replays_cover_weather_changes() {
+    // the map starts breezy and may change every WEATHER_CHANGE_SECONDS, at least one replay has
+    // to run long enough and on a seed that actually changes it
+    let changed = replay_paths().iter().any(|path| {
+        let recorded = load(path);
+        recorded
+            .checkpoints
+            .windows(2)
+            .any(|pair| pair[0].weather != pair[1].weather)
+    });
+    assert!(changed, "no replay records a weather change");
+}